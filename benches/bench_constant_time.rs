@@ -0,0 +1,149 @@
+// A dudect-style statistical timing measurement of the encoding, following
+//     Oscar Reparaz, Josep Balasch, and Ingrid Verbauwhede, "Dude, is my code constant time?", DATE 2017.
+//
+// For the test, run:
+//     cargo bench --bench netherite-bench-constant-time -- --nocapture
+//
+// The inputs are split into two classes, interleaved at random, and Welch's t-test compares the timing
+// distributions, also after cropping the measurements above several percentiles. A |t| above 4.5 is
+// considered a leak; the larger the number of measurements, the smaller the leak that can be detected.
+// The statistics are only printed: nothing fails on them, and the encoding is not claimed to be constant
+// time, since the Montgomery reduction of arkworks branches on its result.
+//
+// Two tests are run for each curve:
+// - fixed-vs-fixed, with an input encoded via x1 against an input encoded via x3, which targets the
+//   branches of the encoding itself;
+// - fixed-vs-random, as in dudect. The field multiplication of arkworks ends with a conditional
+//   subtraction, so this test may pick up a small difference on the smaller fields, since the branch
+//   predictor learns the subtractions of the repeated fixed input.
+
+use ark_ec::bn::BnParameters;
+use ark_ff::UniformRand;
+use ark_std::rand::Rng;
+use netherite_algebra::curve_bn254::Parameters as Bn254Parameters;
+use netherite_algebra::curve_bn446::Parameters as Bn446Parameters;
+use netherite_algebra::message_encoding::Encoder;
+
+const NUM_MEASUREMENTS: usize = 100000;
+const NUM_WARMUP: usize = 1000;
+const T_THRESHOLD: f64 = 4.5;
+
+/// Welford's online mean and variance.
+#[derive(Default, Clone, Copy)]
+struct Moments {
+    n: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Moments {
+    fn push(&mut self, x: f64) {
+        self.n += 1.0;
+        let delta = x - self.mean;
+        self.mean += delta / self.n;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    fn variance(&self) -> f64 {
+        self.m2 / (self.n - 1.0)
+    }
+}
+
+fn welch_t(a: &Moments, b: &Moments) -> f64 {
+    (a.mean - b.mean) / (a.variance() / a.n + b.variance() / b.n).sqrt()
+}
+
+/// Compare the timings of encoding `class_0` against `class_1`, or against random inputs if it is `None`.
+fn dudect_encoding<P: BnParameters>(name: &str, class_0: P::Fp, class_1: Option<P::Fp>) {
+    let mut rng = ark_std::test_rng();
+    let encoder = Encoder::<P::G1Parameters>::new().unwrap();
+
+    let mut classes = Vec::with_capacity(NUM_MEASUREMENTS);
+    let mut inputs = Vec::with_capacity(NUM_MEASUREMENTS);
+    for _ in 0..NUM_MEASUREMENTS {
+        let class = rng.gen::<bool>() as usize;
+        classes.push(class);
+        inputs.push(if class == 0 {
+            class_0
+        } else {
+            class_1.unwrap_or_else(|| P::Fp::rand(&mut rng))
+        });
+    }
+
+    for input in inputs.iter().take(NUM_WARMUP) {
+        let _ = encoder.encode(*input, &mut rng);
+    }
+
+    let mut timings = Vec::with_capacity(NUM_MEASUREMENTS);
+    for input in inputs.iter() {
+        let start = ark_std::time::Instant::now();
+        let res = encoder.encode(*input, &mut rng);
+        timings.push(start.elapsed().as_nanos() as f64);
+        let _ = ark_std::hint::black_box(res);
+    }
+
+    // crop at the percentiles 1 - 0.5^k, as in dudect, in addition to the uncropped test
+    let mut sorted = timings.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let mut cutoffs = vec![f64::INFINITY];
+    for k in 1..10 {
+        let percentile = 1.0 - 0.5f64.powf(k as f64);
+        cutoffs.push(sorted[(percentile * NUM_MEASUREMENTS as f64) as usize]);
+    }
+
+    let mut max_t = 0f64;
+    for cutoff in cutoffs.iter() {
+        let mut moments = [Moments::default(); 2];
+        for (class, timing) in classes.iter().zip(timings.iter()) {
+            if timing < cutoff {
+                moments[*class].push(*timing);
+            }
+        }
+        max_t = max_t.max(welch_t(&moments[0], &moments[1]).abs());
+    }
+
+    println!(
+        "{}: max |t| = {:.2} over {} measurements, {}",
+        name,
+        max_t,
+        NUM_MEASUREMENTS,
+        if max_t < T_THRESHOLD {
+            "no leak detected"
+        } else {
+            "possible leak"
+        }
+    );
+}
+
+/// Find a small input whose encoding has the given hint.
+fn find_input<P: BnParameters>(hints: &[u8]) -> P::Fp {
+    let mut rng = ark_std::test_rng();
+    let encoder = Encoder::<P::G1Parameters>::new().unwrap();
+
+    (1u64..)
+        .map(P::Fp::from)
+        .find(|val| hints.contains(&encoder.encode(*val, &mut rng).1))
+        .unwrap()
+}
+
+fn dudect<P: BnParameters>(curve: &str) {
+    let input_x1 = find_input::<P>(&[1]);
+    let input_x3 = find_input::<P>(&[3, 4]);
+
+    dudect_encoding::<P>(
+        &format!("encoding for {}, x1 vs x3", curve),
+        input_x1,
+        Some(input_x3),
+    );
+    dudect_encoding::<P>(
+        &format!("encoding for {}, fixed vs random", curve),
+        input_x1,
+        None,
+    );
+}
+
+fn main() {
+    dudect::<Bn254Parameters>("BN254");
+    dudect::<Bn446Parameters>("BN446");
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::vec_init_then_push)]
+
 use ark_ec::{AffineCurve, PairingEngine};
 use netherite_algebra::{curve_bn254::Bn254, curve_bn446::Bn446};
 
@@ -11,4 +11,5 @@ pub use fq6::*;
 pub use fr::*;
 
 #[cfg(test)]
+#[allow(clippy::needless_borrows_for_generic_args)]
 mod tests;
@@ -0,0 +1,269 @@
+use crate::elgamal_encryption::{ElGamalPublicKey, ElGamalSecretKey};
+use ark_ec::ProjectiveCurve;
+use ark_ff::{FpParameters, PrimeField};
+use ark_std::cfg_chunks_mut;
+use ark_std::{marker::PhantomData, vec::Vec, UniformRand};
+use blake2::crypto_mac::{Mac, NewMac};
+use blake2::{Blake2b, Digest};
+
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+#[cfg(feature = "parallel")]
+use rayon::slice::ParallelSliceMut;
+
+/// The domain separator of the key derivation function.
+const KDF_DOMAIN: &[u8] = b"netherite-ecies-kdf";
+
+/// The length of a keystream block, which is the output length of Blake2b.
+const BLOCK_LEN: usize = 64;
+
+/// The symmetric keys derived by the KEM, one for the stream cipher and one for the MAC.
+#[derive(Clone)]
+pub struct EciesKey {
+    pub enc_key: [u8; 32],
+    pub mac_key: [u8; 32],
+}
+
+#[derive(Clone)]
+pub struct EciesCiphertext<G: ProjectiveCurve> {
+    pub r: G,
+    pub c: Vec<u8>,
+    pub tag: Vec<u8>,
+}
+
+/// An ECIES-style KEM/DEM for bulk data that does not need to be rerandomized.
+///
+/// The KEM uses the first slot of an ElGamal key pair, so the same key pair serves
+/// both [`crate::elgamal_encryption::ElGamalEncryption`] and this scheme.
+/// The DEM is encrypt-then-MAC, with Blake2b in counter mode as the stream cipher
+/// and keyed Blake2b as the MAC.
+pub struct EciesEncryption<G: ProjectiveCurve> {
+    pub pairing_engine_phantom: PhantomData<G>,
+}
+
+impl<G: ProjectiveCurve> EciesEncryption<G> {
+    pub fn encapsulate<R: ark_std::rand::Rng>(
+        pk: &ElGamalPublicKey<G>,
+        rng: &mut R,
+    ) -> (G, EciesKey) {
+        let scalar_r = G::ScalarField::rand(rng);
+
+        let r = pk.pp.g.mul(scalar_r.into_repr());
+        let shared = pk.y[0].mul(scalar_r.into_repr());
+
+        (r, Self::kdf(&r, &shared))
+    }
+
+    /// Derive the keys from the ephemeral point, or return `None` if it is zero or not in the
+    /// prime-order subgroup, since a point of small order would leak the secret key modulo its order.
+    pub fn decapsulate(sk: &ElGamalSecretKey<G>, r: &G) -> Option<EciesKey> {
+        // the subgroup check of arkworks is only on the affine points of a concrete model, so r is
+        // multiplied by the order of the subgroup
+        let order = <G::ScalarField as PrimeField>::Params::MODULUS;
+        if r.is_zero() || !r.mul(order).is_zero() {
+            return None;
+        }
+
+        let shared = r.mul(sk.scalar_x[0].into_repr());
+
+        Some(Self::kdf(r, &shared))
+    }
+
+    pub fn encrypt<R: ark_std::rand::Rng>(
+        pk: &ElGamalPublicKey<G>,
+        plaintext: &[u8],
+        rng: &mut R,
+    ) -> EciesCiphertext<G> {
+        let (r, key) = Self::encapsulate(pk, rng);
+
+        let mut c = plaintext.to_vec();
+        Self::apply_keystream(&key.enc_key, &mut c);
+
+        let tag = Mac::finalize(Self::mac(&key.mac_key, &r, &c))
+            .into_bytes()
+            .to_vec();
+
+        EciesCiphertext::<G> { r, c, tag }
+    }
+
+    /// Decrypt the ciphertext, or return `None` if the ephemeral point is rejected or the MAC does not verify.
+    pub fn decrypt(sk: &ElGamalSecretKey<G>, ciphertext: &EciesCiphertext<G>) -> Option<Vec<u8>> {
+        let key = Self::decapsulate(sk, &ciphertext.r)?;
+
+        // the comparison of the tags is in constant time
+        Self::mac(&key.mac_key, &ciphertext.r, &ciphertext.c)
+            .verify(&ciphertext.tag)
+            .ok()?;
+
+        let mut plaintext = ciphertext.c.clone();
+        Self::apply_keystream(&key.enc_key, &mut plaintext);
+
+        Some(plaintext)
+    }
+
+    fn kdf(r: &G, shared: &G) -> EciesKey {
+        let mut bytes = Vec::new();
+        r.serialize(&mut bytes).unwrap();
+        shared.serialize(&mut bytes).unwrap();
+
+        let mut hasher = <Blake2b as Digest>::new();
+        Digest::update(&mut hasher, KDF_DOMAIN);
+        Digest::update(&mut hasher, &bytes);
+        let output = Digest::finalize(hasher);
+
+        let mut enc_key = [0u8; 32];
+        let mut mac_key = [0u8; 32];
+        enc_key.copy_from_slice(&output[..32]);
+        mac_key.copy_from_slice(&output[32..]);
+
+        EciesKey { enc_key, mac_key }
+    }
+
+    fn mac(mac_key: &[u8; 32], r: &G, c: &[u8]) -> Blake2b {
+        let mut bytes = Vec::new();
+        r.serialize(&mut bytes).unwrap();
+
+        let mut mac = Blake2b::new_varkey(mac_key).unwrap();
+        Mac::update(&mut mac, &bytes);
+        Mac::update(&mut mac, &(c.len() as u64).to_le_bytes());
+        Mac::update(&mut mac, c);
+        mac
+    }
+
+    fn apply_keystream(enc_key: &[u8; 32], data: &mut [u8]) {
+        cfg_chunks_mut!(data, BLOCK_LEN)
+            .enumerate()
+            .for_each(|(i, chunk)| {
+                let mut prf = Blake2b::new_varkey(enc_key).unwrap();
+                Mac::update(&mut prf, &(i as u64).to_le_bytes());
+                let block = Mac::finalize(prf).into_bytes();
+
+                for (byte, key_byte) in chunk.iter_mut().zip(block.iter()) {
+                    *byte ^= key_byte;
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ecies::{EciesCiphertext, EciesEncryption};
+    use crate::elgamal_encryption::ElGamalEncryption;
+    use ark_bls12_381::{Fq, Fr, G1Affine, G1Projective};
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::{FpParameters, PrimeField, Zero};
+    use ark_std::rand::RngCore;
+    use ark_std::UniformRand;
+    use blake2::crypto_mac::Mac;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let mut rng = ark_std::test_rng();
+
+        let pp = ElGamalEncryption::<G1Projective>::setup(&mut rng);
+        let (sk, pk) = ElGamalEncryption::<G1Projective>::key_generation(&pp, 1, &mut rng);
+
+        for len in [0usize, 1, 63, 64, 65, 100000].iter() {
+            let mut pt = vec![0u8; *len];
+            rng.fill_bytes(&mut pt);
+
+            let ct = EciesEncryption::encrypt(&pk, &pt, &mut rng);
+            let pt_recovered = EciesEncryption::decrypt(&sk, &ct).unwrap();
+
+            assert_eq!(pt, pt_recovered);
+        }
+    }
+
+    #[test]
+    fn test_shared_key_pair() {
+        let mut rng = ark_std::test_rng();
+        let len = 4;
+
+        let pp = ElGamalEncryption::<G1Projective>::setup(&mut rng);
+        let (sk, pk) = ElGamalEncryption::<G1Projective>::key_generation(&pp, len, &mut rng);
+
+        let mut pt = Vec::new();
+        for _ in 0..len {
+            pt.push(G1Projective::rand(&mut rng));
+        }
+        let ct = ElGamalEncryption::encrypt(&pk, &pt, &mut rng);
+        let pt_recovered = ElGamalEncryption::decrypt(&sk, &ct);
+        for i in 0..len {
+            assert!(pt[i].eq(&pt_recovered[i]));
+        }
+
+        let bulk = vec![7u8; 1000];
+        let ct = EciesEncryption::encrypt(&pk, &bulk, &mut rng);
+        assert_eq!(EciesEncryption::decrypt(&sk, &ct).unwrap(), bulk);
+    }
+
+    #[test]
+    fn test_tampered_ciphertext() {
+        let mut rng = ark_std::test_rng();
+
+        let pp = ElGamalEncryption::<G1Projective>::setup(&mut rng);
+        let (sk, pk) = ElGamalEncryption::<G1Projective>::key_generation(&pp, 1, &mut rng);
+
+        let mut pt = vec![0u8; 1000];
+        rng.fill_bytes(&mut pt);
+        let ct = EciesEncryption::encrypt(&pk, &pt, &mut rng);
+
+        let mut ct_tampered = ct.clone();
+        ct_tampered.c[500] ^= 1;
+        assert!(EciesEncryption::decrypt(&sk, &ct_tampered).is_none());
+
+        let mut ct_tampered = ct.clone();
+        ct_tampered.c.pop();
+        assert!(EciesEncryption::decrypt(&sk, &ct_tampered).is_none());
+
+        let mut ct_tampered = ct.clone();
+        ct_tampered.r += pp.g;
+        assert!(EciesEncryption::decrypt(&sk, &ct_tampered).is_none());
+
+        let mut ct_tampered = ct;
+        ct_tampered.tag[0] ^= 1;
+        assert!(EciesEncryption::decrypt(&sk, &ct_tampered).is_none());
+    }
+
+    #[test]
+    fn test_small_order_r() {
+        let mut rng = ark_std::test_rng();
+
+        let pp = ElGamalEncryption::<G1Projective>::setup(&mut rng);
+        let (sk, pk) = ElGamalEncryption::<G1Projective>::key_generation(&pp, 1, &mut rng);
+
+        let pt = vec![7u8; 100];
+        let ct = EciesEncryption::encrypt(&pk, &pt, &mut rng);
+
+        // a point of the curve outside of the subgroup, and a point of small order,
+        // which is that point multiplied by the order of the subgroup
+        let (off_subgroup, small_order) = (1u64..)
+            .filter_map(|x| G1Affine::get_point_from_x(Fq::from(x), true))
+            .map(|p| (p, p.mul(<Fr as PrimeField>::Params::MODULUS)))
+            .find(|(_, q)| !q.is_zero())
+            .unwrap();
+        assert!(!off_subgroup.is_in_correct_subgroup_assuming_on_curve());
+
+        for r in [
+            G1Projective::zero(),
+            off_subgroup.into_projective(),
+            small_order,
+        ]
+        .iter()
+        {
+            assert!(EciesEncryption::decapsulate(&sk, r).is_none());
+
+            // the tag is valid for a sender that guessed the shared point
+            let key = EciesEncryption::kdf(r, &r.mul(sk.scalar_x[0].into_repr()));
+            let mut c = pt.clone();
+            EciesEncryption::<G1Projective>::apply_keystream(&key.enc_key, &mut c);
+            let tag = Mac::finalize(EciesEncryption::mac(&key.mac_key, r, &c))
+                .into_bytes()
+                .to_vec();
+            let ct_tampered = EciesCiphertext { r: *r, c, tag };
+            assert!(EciesEncryption::decrypt(&sk, &ct_tampered).is_none());
+        }
+
+        assert_eq!(EciesEncryption::decrypt(&sk, &ct).unwrap(), pt);
+    }
+}
@@ -108,6 +108,121 @@ impl<G: ProjectiveCurve> ElGamalEncryption<G> {
 
         ElGamalCiphertext::<G> { r: r_new, e: e_new }
     }
+
+    /// Keep only the slots at `indices`, in the given order. The result decrypts under the
+    /// matching projection of the keys, see [`Self::project_secret_key`].
+    pub fn project(ciphertext: &ElGamalCiphertext<G>, indices: &[usize]) -> ElGamalCiphertext<G> {
+        let mut e = Vec::<G>::new();
+        for i in indices.iter() {
+            e.push(ciphertext.e[*i]);
+        }
+
+        ElGamalCiphertext::<G> { r: ciphertext.r, e }
+    }
+
+    pub fn project_public_key(pk: &ElGamalPublicKey<G>, indices: &[usize]) -> ElGamalPublicKey<G> {
+        let mut y = Vec::<G>::new();
+        for i in indices.iter() {
+            y.push(pk.y[*i]);
+        }
+
+        ElGamalPublicKey::<G> {
+            pp: pk.pp.clone(),
+            y,
+        }
+    }
+
+    pub fn project_secret_key(sk: &ElGamalSecretKey<G>, indices: &[usize]) -> ElGamalSecretKey<G> {
+        let mut scalar_x = Vec::<G::ScalarField>::new();
+        for i in indices.iter() {
+            scalar_x.push(sk.scalar_x[*i]);
+        }
+
+        ElGamalSecretKey::<G> { scalar_x }
+    }
+
+    /// Concatenate two ciphertexts under the concatenation of their keys.
+    ///
+    /// All slots of a ciphertext share one randomness, so this only applies to ciphertexts
+    /// with the same `r`, such as projections of one ciphertext.
+    pub fn concat(
+        ciphertext_1: &ElGamalCiphertext<G>,
+        ciphertext_2: &ElGamalCiphertext<G>,
+    ) -> ElGamalCiphertext<G> {
+        assert!(ciphertext_1.r == ciphertext_2.r);
+
+        let mut e = ciphertext_1.e.clone();
+        e.extend_from_slice(&ciphertext_2.e);
+
+        ElGamalCiphertext::<G> {
+            r: ciphertext_1.r,
+            e,
+        }
+    }
+
+    pub fn concat_public_keys(
+        pk_1: &ElGamalPublicKey<G>,
+        pk_2: &ElGamalPublicKey<G>,
+    ) -> ElGamalPublicKey<G> {
+        assert!(pk_1.pp.g == pk_2.pp.g);
+
+        let mut y = pk_1.y.clone();
+        y.extend_from_slice(&pk_2.y);
+
+        ElGamalPublicKey::<G> {
+            pp: pk_1.pp.clone(),
+            y,
+        }
+    }
+
+    pub fn concat_secret_keys(
+        sk_1: &ElGamalSecretKey<G>,
+        sk_2: &ElGamalSecretKey<G>,
+    ) -> ElGamalSecretKey<G> {
+        let mut scalar_x = sk_1.scalar_x.clone();
+        scalar_x.extend_from_slice(&sk_2.scalar_x);
+
+        ElGamalSecretKey::<G> { scalar_x }
+    }
+
+    /// Permute the slots, so that slot `i` of the result is slot `perm[i]` of the input.
+    /// The result decrypts under the keys permuted in the same way.
+    pub fn permute(ciphertext: &ElGamalCiphertext<G>, perm: &[usize]) -> ElGamalCiphertext<G> {
+        assert_permutation(perm, ciphertext.e.len());
+        Self::project(ciphertext, perm)
+    }
+
+    pub fn permute_public_key(pk: &ElGamalPublicKey<G>, perm: &[usize]) -> ElGamalPublicKey<G> {
+        assert_permutation(perm, pk.y.len());
+        Self::project_public_key(pk, perm)
+    }
+
+    pub fn permute_secret_key(sk: &ElGamalSecretKey<G>, perm: &[usize]) -> ElGamalSecretKey<G> {
+        assert_permutation(perm, sk.scalar_x.len());
+        Self::project_secret_key(sk, perm)
+    }
+
+    /// Decrypt a ciphertext that only contains the slots at `indices`, e.g., one obtained
+    /// from [`Self::project`] after a partial download, using the matching secret scalars.
+    pub fn decrypt_slots(
+        sk: &ElGamalSecretKey<G>,
+        ciphertext: &ElGamalCiphertext<G>,
+        indices: &[usize],
+    ) -> Vec<G> {
+        assert_eq!(ciphertext.e.len(), indices.len());
+        Self::decrypt(&Self::project_secret_key(sk, indices), ciphertext)
+    }
+}
+
+/// Check that `perm` is a permutation of `0..len`.
+pub(crate) fn assert_permutation(perm: &[usize], len: usize) {
+    assert_eq!(perm.len(), len);
+
+    let mut seen = vec![false; len];
+    for i in perm.iter() {
+        assert!(*i < len && !seen[*i], "Not a permutation.");
+        seen[*i] = true;
+    }
 }
 
 #[cfg(test)]
@@ -164,4 +279,75 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn test_slot_operations() {
+        let mut rng = ark_std::test_rng();
+        let len = 10;
+
+        let mut pt = Vec::new();
+        for _ in 0..len {
+            pt.push(G1Projective::rand(&mut rng));
+        }
+
+        let pp = ElGamalEncryption::<G1Projective>::setup(&mut rng);
+        let (sk, pk) = ElGamalEncryption::<G1Projective>::key_generation(&pp, len, &mut rng);
+
+        let ct = ElGamalEncryption::encrypt(&pk, &pt, &mut rng);
+
+        // partial download of a subset of the slots
+        let indices = [7, 2, 5];
+        let ct_projected = ElGamalEncryption::project(&ct, &indices);
+        let pt_recovered = ElGamalEncryption::decrypt_slots(&sk, &ct_projected, &indices);
+        for (j, i) in indices.iter().enumerate() {
+            assert!(
+                pt[*i].eq(&pt_recovered[j]),
+                "Decrypted results of projected ciphertexts do not match the plaintexts."
+            );
+        }
+
+        // concatenation of two projections under the concatenated keys
+        let left = [0, 1, 2, 3];
+        let right = [4, 5, 6, 7, 8, 9];
+        let ct_concat = ElGamalEncryption::concat(
+            &ElGamalEncryption::project(&ct, &left),
+            &ElGamalEncryption::project(&ct, &right),
+        );
+        let sk_concat = ElGamalEncryption::concat_secret_keys(
+            &ElGamalEncryption::project_secret_key(&sk, &left),
+            &ElGamalEncryption::project_secret_key(&sk, &right),
+        );
+        let pt_recovered = ElGamalEncryption::decrypt(&sk_concat, &ct_concat);
+        for i in 0..len {
+            assert!(
+                pt[i].eq(&pt_recovered[i]),
+                "Decrypted results of concatenated ciphertexts do not match the plaintexts."
+            );
+        }
+
+        // permutation, followed by rerandomization under the permuted public key
+        let perm = [3, 9, 0, 1, 8, 2, 7, 4, 6, 5];
+        let pk_perm = ElGamalEncryption::permute_public_key(&pk, &perm);
+        let sk_perm = ElGamalEncryption::permute_secret_key(&sk, &perm);
+        let ct_perm =
+            ElGamalEncryption::rerand(&pk_perm, &ElGamalEncryption::permute(&ct, &perm), &mut rng);
+        let pt_recovered = ElGamalEncryption::decrypt(&sk_perm, &ct_perm);
+        for i in 0..len {
+            assert!(
+                pt[perm[i]].eq(&pt_recovered[i]),
+                "Decrypted results of permuted ciphertexts do not match the plaintexts."
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_permutation() {
+        let mut rng = ark_std::test_rng();
+
+        let pp = ElGamalEncryption::<G1Projective>::setup(&mut rng);
+        let (_, pk) = ElGamalEncryption::<G1Projective>::key_generation(&pp, 3, &mut rng);
+
+        ElGamalEncryption::permute_public_key(&pk, &[0, 2, 2]);
+    }
 }
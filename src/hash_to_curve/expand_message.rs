@@ -0,0 +1,198 @@
+//! The `expand_message` variants of RFC 9380, Section 5.3.
+
+use crate::hash_to_curve::HashToCurveError;
+use ark_std::vec::Vec;
+use digest::generic_array::typenum::Unsigned;
+use digest::{BlockInput, Digest, ExtendableOutput, Update, XofReader};
+
+/// The prefix of the hashed domain separation tags that are longer than 255 bytes, RFC 9380, Section 5.3.3.
+const OVERSIZE_DST_PREFIX: &[u8] = b"H2C-OVERSIZE-DST-";
+
+fn check_parameters(dst: &[u8], len_in_bytes: usize) -> Result<(), HashToCurveError> {
+    if dst.is_empty() {
+        return Err(HashToCurveError::EmptyDst);
+    }
+    if len_in_bytes > u16::MAX as usize {
+        return Err(HashToCurveError::OutputTooLong(len_in_bytes));
+    }
+    Ok(())
+}
+
+/// `expand_message_xmd` with a Merkle-Damgard hash function H, RFC 9380, Section 5.3.1.
+pub fn expand_message_xmd<H: Digest + BlockInput>(
+    msg: &[u8],
+    dst: &[u8],
+    len_in_bytes: usize,
+) -> Result<Vec<u8>, HashToCurveError> {
+    check_parameters(dst, len_in_bytes)?;
+
+    let b_in_bytes = H::OutputSize::to_usize();
+    let r_in_bytes = H::BlockSize::to_usize();
+
+    let ell = len_in_bytes.div_ceil(b_in_bytes);
+    if ell > 255 {
+        return Err(HashToCurveError::OutputTooLong(len_in_bytes));
+    }
+
+    let dst = if dst.len() > 255 {
+        let mut hasher = H::new();
+        Digest::update(&mut hasher, OVERSIZE_DST_PREFIX);
+        Digest::update(&mut hasher, dst);
+        hasher.finalize().to_vec()
+    } else {
+        dst.to_vec()
+    };
+    let mut dst_prime = dst;
+    dst_prime.push(dst_prime.len() as u8);
+
+    // b_0 = H(Z_pad || msg || l_i_b_str || I2OSP(0, 1) || DST_prime)
+    let mut hasher = H::new();
+    Digest::update(&mut hasher, vec![0u8; r_in_bytes]);
+    Digest::update(&mut hasher, msg);
+    Digest::update(&mut hasher, (len_in_bytes as u16).to_be_bytes());
+    Digest::update(&mut hasher, [0u8]);
+    Digest::update(&mut hasher, &dst_prime);
+    let b_0 = hasher.finalize();
+
+    // b_i = H(strxor(b_0, b_(i - 1)) || I2OSP(i, 1) || DST_prime), with b_1 = H(b_0 || I2OSP(1, 1) || DST_prime)
+    let mut uniform_bytes = Vec::with_capacity(ell * b_in_bytes);
+    let mut b_i = vec![0u8; b_in_bytes];
+    for i in 1..=ell {
+        let mut hasher = H::new();
+        let xored: Vec<u8> = b_0.iter().zip(b_i.iter()).map(|(a, b)| a ^ b).collect();
+        Digest::update(&mut hasher, xored);
+        Digest::update(&mut hasher, [i as u8]);
+        Digest::update(&mut hasher, &dst_prime);
+        b_i = hasher.finalize().to_vec();
+
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+    uniform_bytes.truncate(len_in_bytes);
+
+    Ok(uniform_bytes)
+}
+
+/// `expand_message_xof` with an extendable-output function H at the security level k, RFC 9380, Section 5.3.2.
+pub fn expand_message_xof<H: Default + Update + ExtendableOutput>(
+    msg: &[u8],
+    dst: &[u8],
+    len_in_bytes: usize,
+    k: usize,
+) -> Result<Vec<u8>, HashToCurveError> {
+    check_parameters(dst, len_in_bytes)?;
+
+    let dst = if dst.len() > 255 {
+        let mut hasher = H::default();
+        hasher.update(OVERSIZE_DST_PREFIX);
+        hasher.update(dst);
+
+        let mut res = vec![0u8; (2 * k).div_ceil(8)];
+        hasher.finalize_xof().read(&mut res);
+        res
+    } else {
+        dst.to_vec()
+    };
+
+    let mut hasher = H::default();
+    hasher.update(msg);
+    hasher.update((len_in_bytes as u16).to_be_bytes());
+    hasher.update(&dst);
+    hasher.update([dst.len() as u8]);
+
+    let mut uniform_bytes = vec![0u8; len_in_bytes];
+    hasher.finalize_xof().read(&mut uniform_bytes);
+
+    Ok(uniform_bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash_to_curve::expand_message::{expand_message_xmd, expand_message_xof};
+    use crate::hash_to_curve::HashToCurveError;
+    use sha2::Sha256;
+    use sha3::Shake128;
+
+    fn from_hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_message_xmd() {
+        // RFC 9380, Appendix K.1
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        let vectors = [
+            (
+                &b""[..],
+                "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235",
+            ),
+            (
+                &b"abc"[..],
+                "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615",
+            ),
+            (
+                &b"abcdef0123456789"[..],
+                "eff31487c770a893cfb36f912fbfcbff40d5661771ca4b2cb4eafe524333f5c1",
+            ),
+        ];
+        for (msg, expected) in vectors.iter() {
+            assert_eq!(
+                expand_message_xmd::<Sha256>(msg, dst, 0x20).unwrap(),
+                from_hex(expected)
+            );
+        }
+
+        let res = expand_message_xmd::<Sha256>(b"abc", dst, 0x80).unwrap();
+        assert_eq!(res.len(), 0x80);
+        assert_eq!(
+            res[..0x20],
+            from_hex("abba86a6129e366fc877aab32fc4ffc70120d8996c88aee2fe4b32d6c7b6437a")[..]
+        );
+
+        // the long tags are hashed
+        let long_dst = [b'a'; 256];
+        let res = expand_message_xmd::<Sha256>(b"abc", &long_dst, 0x20).unwrap();
+        assert_ne!(
+            res,
+            expand_message_xmd::<Sha256>(b"abc", &long_dst[..255], 0x20).unwrap()
+        );
+
+        assert_eq!(
+            expand_message_xmd::<Sha256>(b"abc", b"", 0x20),
+            Err(HashToCurveError::EmptyDst)
+        );
+        assert_eq!(
+            expand_message_xmd::<Sha256>(b"abc", dst, 255 * 32 + 1),
+            Err(HashToCurveError::OutputTooLong(255 * 32 + 1))
+        );
+    }
+
+    #[test]
+    fn test_expand_message_xof() {
+        // RFC 9380, Appendix K.3
+        let dst = b"QUUX-V01-CS02-with-expander-SHAKE128";
+        let vectors = [
+            (
+                &b""[..],
+                "86518c9cd86581486e9485aa74ab35ba150d1c75c88e26b7043e44e2acd735a2",
+            ),
+            (
+                &b"abc"[..],
+                "8696af52a4d862417c0763556073f47bc9b9ba43c99b505305cb1ec04a9ab468",
+            ),
+        ];
+        for (msg, expected) in vectors.iter() {
+            assert_eq!(
+                expand_message_xof::<Shake128>(msg, dst, 0x20, 128).unwrap(),
+                from_hex(expected)
+            );
+        }
+
+        assert_eq!(
+            expand_message_xof::<Shake128>(b"abc", dst, 0x10000, 128),
+            Err(HashToCurveError::OutputTooLong(0x10000))
+        );
+    }
+}
@@ -0,0 +1,533 @@
+//! Hashing to G1 and G2 of the BN curves, following RFC 9380, "Hashing to Elliptic Curves".
+//!
+//! The suites use `expand_message_xmd` with SHA-256, `hash_to_field` with a security level of 128 bits,
+//! and the Shallue-van de Woestijne map, followed by cofactor clearing.
+//!
+//! The BN254 curve of this crate is not the BN254 curve of RFC 9380, so the suite IDs are prefixed with
+//! `NETHERITE-` to keep them apart from the suites of the RFC.
+
+use ark_ec::short_weierstrass_jacobian::GroupAffine;
+use ark_ec::{AffineCurve, SWModelParameters};
+use ark_ff::fields::{Fp2, Fp256, Fp256Parameters, Fp2Parameters, Fp448, Fp448Parameters};
+use ark_ff::{BigInteger, Field, FpParameters, PrimeField, SquareRootField, Zero};
+use ark_std::{fmt, vec::Vec};
+use sha2::Sha256;
+use sha3::Shake128;
+
+pub mod expand_message;
+pub mod svdw;
+
+use expand_message::{expand_message_xmd, expand_message_xof};
+use svdw::SvdWMap;
+
+/// The target security level k, in bits.
+pub const SECURITY_LEVEL: usize = 128;
+
+/// The errors of `expand_message` for invalid parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashToCurveError {
+    /// The domain separation tag is empty.
+    EmptyDst,
+    /// The requested output length is too long for `expand_message`.
+    OutputTooLong(usize),
+}
+
+impl fmt::Display for HashToCurveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashToCurveError::EmptyDst => write!(f, "the domain separation tag is empty"),
+            HashToCurveError::OutputTooLong(len) => {
+                write!(f, "the output length {} is too long", len)
+            }
+        }
+    }
+}
+
+impl ark_std::error::Error for HashToCurveError {}
+
+/// The sign of a field element, `sgn0` of RFC 9380, Section 4.1.
+pub trait SignedField: SquareRootField {
+    fn sgn0(&self) -> bool;
+}
+
+impl<P: Fp256Parameters> SignedField for Fp256<P> {
+    fn sgn0(&self) -> bool {
+        self.into_repr().is_odd()
+    }
+}
+
+impl<P: Fp448Parameters> SignedField for Fp448<P> {
+    fn sgn0(&self) -> bool {
+        self.into_repr().is_odd()
+    }
+}
+
+impl<P: Fp2Parameters> SignedField for Fp2<P>
+where
+    P::Fp: SignedField,
+{
+    fn sgn0(&self) -> bool {
+        self.c0.sgn0() || (self.c0.is_zero() && self.c1.sgn0())
+    }
+}
+
+/// The `expand_message` variants of RFC 9380, Section 5.3.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExpandMessage {
+    XmdSha256,
+    XofShake128,
+}
+
+impl ExpandMessage {
+    pub fn expand(
+        &self,
+        msg: &[u8],
+        dst: &[u8],
+        len_in_bytes: usize,
+    ) -> Result<Vec<u8>, HashToCurveError> {
+        match self {
+            ExpandMessage::XmdSha256 => expand_message_xmd::<Sha256>(msg, dst, len_in_bytes),
+            ExpandMessage::XofShake128 => {
+                expand_message_xof::<Shake128>(msg, dst, len_in_bytes, SECURITY_LEVEL)
+            }
+        }
+    }
+}
+
+/// Hash the message to `count` elements of F, RFC 9380, Section 5.2.
+pub fn hash_to_field<F: Field>(
+    msg: &[u8],
+    dst: &[u8],
+    count: usize,
+    expander: ExpandMessage,
+) -> Result<Vec<F>, HashToCurveError> {
+    let modulus_bits = <F::BasePrimeField as PrimeField>::Params::MODULUS_BITS as usize;
+    let len_per_elem = (modulus_bits + SECURITY_LEVEL).div_ceil(8);
+    let degree = F::extension_degree() as usize;
+
+    let uniform_bytes = expander.expand(msg, dst, count * degree * len_per_elem)?;
+
+    Ok(uniform_bytes
+        .chunks(degree * len_per_elem)
+        .map(|chunk| {
+            let elems: Vec<F::BasePrimeField> = chunk
+                .chunks(len_per_elem)
+                .map(F::BasePrimeField::from_be_bytes_mod_order)
+                .collect();
+            F::from_base_prime_field_elems(&elems).unwrap()
+        })
+        .collect())
+}
+
+/// The suite IDs of a curve, RFC 9380, Section 8.10.
+pub trait SuiteParameters: SWModelParameters {
+    /// The ID of the random-oracle suite.
+    const RO_ID: &'static str;
+    /// The ID of the nonuniform suite.
+    const NU_ID: &'static str;
+}
+
+impl SuiteParameters for crate::curve_bn254::g1::Parameters {
+    const RO_ID: &'static str = "NETHERITE-BN254G1_XMD:SHA-256_SVDW_RO_";
+    const NU_ID: &'static str = "NETHERITE-BN254G1_XMD:SHA-256_SVDW_NU_";
+}
+
+impl SuiteParameters for crate::curve_bn254::g2::Parameters {
+    const RO_ID: &'static str = "NETHERITE-BN254G2_XMD:SHA-256_SVDW_RO_";
+    const NU_ID: &'static str = "NETHERITE-BN254G2_XMD:SHA-256_SVDW_NU_";
+}
+
+impl SuiteParameters for crate::curve_bn446::g1::Parameters {
+    const RO_ID: &'static str = "NETHERITE-BN446G1_XMD:SHA-256_SVDW_RO_";
+    const NU_ID: &'static str = "NETHERITE-BN446G1_XMD:SHA-256_SVDW_NU_";
+}
+
+impl SuiteParameters for crate::curve_bn446::g2::Parameters {
+    const RO_ID: &'static str = "NETHERITE-BN446G2_XMD:SHA-256_SVDW_RO_";
+    const NU_ID: &'static str = "NETHERITE-BN446G2_XMD:SHA-256_SVDW_NU_";
+}
+
+pub struct Suite<P: SuiteParameters>
+where
+    P::BaseField: SignedField,
+{
+    pub id: &'static str,
+    pub expander: ExpandMessage,
+    /// Whether the suite is a random oracle, `hash_to_curve`, or nonuniform, `encode_to_curve`.
+    pub random_oracle: bool,
+    pub map: SvdWMap<P>,
+}
+
+impl<P: SuiteParameters> Suite<P>
+where
+    P::BaseField: SignedField,
+{
+    /// The `hash_to_curve` suite, which is indifferentiable from a random oracle.
+    pub fn random_oracle() -> Self {
+        Self {
+            id: P::RO_ID,
+            expander: ExpandMessage::XmdSha256,
+            random_oracle: true,
+            map: SvdWMap::new(),
+        }
+    }
+
+    /// The `encode_to_curve` suite, whose outputs are not uniformly distributed.
+    pub fn nonuniform() -> Self {
+        Self {
+            id: P::NU_ID,
+            expander: ExpandMessage::XmdSha256,
+            random_oracle: false,
+            map: SvdWMap::new(),
+        }
+    }
+
+    /// The domain separation tag of an application, which is the tag followed by `-with-` and the suite ID,
+    /// as recommended in RFC 9380, Section 3.1.
+    pub fn dst(&self, tag: &[u8]) -> Vec<u8> {
+        [tag, b"-with-", self.id.as_bytes()].concat()
+    }
+
+    /// Hash the message into the prime-order subgroup.
+    ///
+    /// The cofactor is cleared by a scalar multiplication by the whole cofactor. It is one on G1, but
+    /// about the size of q on G2, so the multiplication dominates the cost of hashing to G2. Clearing
+    /// with the psi endomorphism, as in Budroni and Pintore, would be faster, but would change the outputs.
+    pub fn hash(&self, msg: &[u8], dst: &[u8]) -> Result<GroupAffine<P>, HashToCurveError> {
+        let count = if self.random_oracle { 2 } else { 1 };
+        let u = hash_to_field::<P::BaseField>(msg, dst, count, self.expander)?;
+
+        let q = u
+            .into_iter()
+            .map(|u| self.map.map_to_curve(u))
+            .fold(GroupAffine::<P>::zero(), |acc, p| acc + p);
+
+        Ok(q.mul_by_cofactor())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::curve_bn254::{
+        g1::Parameters as Bn254G1Parameters, g2::Parameters as Bn254G2Parameters, Fq as Bn254Fq,
+        Fq2 as Bn254Fq2,
+    };
+    use crate::curve_bn446::{
+        g1::Parameters as Bn446G1Parameters, g2::Parameters as Bn446G2Parameters, Fq as Bn446Fq,
+    };
+    use crate::hash_to_curve::{
+        hash_to_field, ExpandMessage, HashToCurveError, SignedField, Suite, SuiteParameters,
+    };
+    use ark_ec::short_weierstrass_jacobian::GroupAffine;
+    use ark_ec::SWModelParameters;
+    use ark_ff::{QuadExtField, QuadExtParameters};
+    use ark_std::{str::FromStr, UniformRand};
+
+    const REPETITIONS: u64 = 10;
+
+    /// The tag of the test values. The suites are not in RFC 9380, so the values are regression values
+    /// of this implementation, which `sage_scripts/hash_to_curve.sage` recomputes independently.
+    const TAG: &[u8] = b"NETHERITE-V01-TEST";
+
+    fn check_suite<P: SuiteParameters>(random_oracle: bool, vectors: &[(&[u8], GroupAffine<P>)])
+    where
+        P::BaseField: SignedField,
+    {
+        let mut rng = ark_std::test_rng();
+        let suite = if random_oracle {
+            Suite::<P>::random_oracle()
+        } else {
+            Suite::<P>::nonuniform()
+        };
+        let dst = suite.dst(TAG);
+
+        for (msg, expected) in vectors.iter() {
+            assert_eq!(suite.hash(msg, &dst).unwrap(), *expected);
+        }
+
+        for _ in 0..REPETITIONS {
+            let msg = u64::rand(&mut rng).to_le_bytes();
+            let p = suite.hash(&msg, &dst).unwrap();
+            assert!(p.is_on_curve());
+            assert!(p.is_in_correct_subgroup_assuming_on_curve());
+        }
+
+        assert_eq!(suite.hash(b"abc", b""), Err(HashToCurveError::EmptyDst));
+    }
+
+    fn g1_point<P: SWModelParameters>(x: &str, y: &str) -> GroupAffine<P>
+    where
+        P::BaseField: FromStr,
+    {
+        GroupAffine::new(
+            P::BaseField::from_str(x).ok().unwrap(),
+            P::BaseField::from_str(y).ok().unwrap(),
+            false,
+        )
+    }
+
+    fn g2_point<Q: QuadExtParameters, P: SWModelParameters<BaseField = QuadExtField<Q>>>(
+        x: [&str; 2],
+        y: [&str; 2],
+    ) -> GroupAffine<P>
+    where
+        Q::BaseField: FromStr,
+    {
+        let elem = |c: [&str; 2]| {
+            QuadExtField::<Q>::new(
+                Q::BaseField::from_str(c[0]).ok().unwrap(),
+                Q::BaseField::from_str(c[1]).ok().unwrap(),
+            )
+        };
+        GroupAffine::new(elem(x), elem(y), false)
+    }
+
+    #[test]
+    fn test_hash_to_field() {
+        let dst = b"NETHERITE-V01-TEST-with-expander";
+
+        // regression values, which `sage_scripts/hash_to_curve.sage` recomputes independently
+
+        let u = hash_to_field::<Bn446Fq>(b"abc", dst, 2, ExpandMessage::XmdSha256).unwrap();
+        assert_eq!(
+            u,
+            vec![
+                Bn446Fq::from_str("98626215161844880702391753188970622109767827907561777298865469007350856665609570699303865895959254164858129659675077247057939680591991").ok().unwrap(),
+                Bn446Fq::from_str("25319811428770937122636609040082938752863592757176961173940785624991624892924984131997149011473003690385823020235093330346262205269139").ok().unwrap(),
+            ]
+        );
+
+        let u = hash_to_field::<Bn254Fq2>(b"abc", dst, 1, ExpandMessage::XofShake128).unwrap();
+        assert_eq!(
+            u,
+            vec![Bn254Fq2::new(
+                Bn254Fq::from_str(
+                    "15517791963770126718379738203104828286345354802673803401091184679651942352362"
+                )
+                .ok()
+                .unwrap(),
+                Bn254Fq::from_str(
+                    "8496006464666022436471849610696982623246542974490744997219138165043837005644"
+                )
+                .ok()
+                .unwrap(),
+            )]
+        );
+    }
+
+    #[test]
+    fn test_bn254_g1_suites() {
+        check_suite::<Bn254G1Parameters>(
+            true,
+            &[
+                (
+                    b"",
+                    g1_point(
+                        "5833939862669310870405143592654115375709204403378088479416069856389355800065",
+                        "7177908910330150608235499740571932153818403079875019689418809979959347103093",
+                    ),
+                ),
+                (
+                    b"abc",
+                    g1_point(
+                        "3393723668483244862346084325570410739649895048951891675898241788059632357809",
+                        "3274549984231077045813476333447907265598451942012003653439776942008958188543",
+                    ),
+                ),
+            ],
+        );
+        check_suite::<Bn254G1Parameters>(
+            false,
+            &[
+                (
+                    b"",
+                    g1_point(
+                        "14948975088001134555874305519538531126924888565213742477490572334036033979102",
+                        "14403446402151543553824124326097101642107952303780992473210713862715566426347",
+                    ),
+                ),
+                (
+                    b"abc",
+                    g1_point(
+                        "1502603537211287747598627626840993067012856263261261874452680468074477410594",
+                        "16668218606273830171280155876347978586946553523319085184992863424568725156491",
+                    ),
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_bn446_g1_suites() {
+        check_suite::<Bn446G1Parameters>(
+            true,
+            &[
+                (
+                    b"",
+                    g1_point(
+                        "55930380258250716554175722026726233460752715081136500265206000807780372225817055587211454687256484068852335372071079203196883581793678",
+                        "53827331145585422977924364199827585203479119846705156791710401647539704801135643407038437910593966536748665528434945924109694741533216",
+                    ),
+                ),
+                (
+                    b"abc",
+                    g1_point(
+                        "5551880957477113519716400394478766275713539873450645779690291736462906961615812816657888007999591023534903744049731249115544297552243",
+                        "3214089996822431392425902361254471906605766448071567719598755336690369195341690391304552291371105396730875884332077086004918257254515",
+                    ),
+                ),
+            ],
+        );
+        check_suite::<Bn446G1Parameters>(
+            false,
+            &[
+                (
+                    b"",
+                    g1_point(
+                        "15131341836346348622846768346324765935846290964326374002159820372585874626710157015027499331980083266958646906463207099166302874821998",
+                        "69086079007210752210513812070036716824133425271974014178361674933100329981149822022577226822236595440428871994751355334302573436375595",
+                    ),
+                ),
+                (
+                    b"abc",
+                    g1_point(
+                        "95805224115094095326574219853458942554659106295752983448329152529654238677741924986015541647977192189400195344234804442771282569682169",
+                        "28944401643149072514009778339427259465001233511834752976904267341492555421994436123966541047881221900567475993970855039138261048380526",
+                    ),
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_bn254_g2_suites() {
+        check_suite::<Bn254G2Parameters>(
+            true,
+            &[
+                (
+                    b"",
+                    g2_point(
+                        [
+                            "9281168630594259558992114266046525547069352030529796644222776150141173919585",
+                            "6025687213062093155165081538759023107323392397698613719520381821186924107148",
+                        ],
+                        [
+                            "11623163778419746139410942047493537143799967305748954798503515016243443989784",
+                            "7968581164165289361435694134703389279313116575081950797851619460753752159634",
+                        ],
+                    ),
+                ),
+                (
+                    b"abc",
+                    g2_point(
+                        [
+                            "2231997411042761064544434641481375180912130019638217493427801211247868946783",
+                            "13609620850406035546512589420268940900426032143638491529698957159999674341690",
+                        ],
+                        [
+                            "9583819014931580461471344124374141807428252129308716145737775497526947007303",
+                            "10579084404541033061831208688207583948536681089717004173342377668747544034730",
+                        ],
+                    ),
+                ),
+            ],
+        );
+        check_suite::<Bn254G2Parameters>(
+            false,
+            &[
+                (
+                    b"",
+                    g2_point(
+                        [
+                            "15604773909518026766332744696577125023091961323223002437145964053870466332468",
+                            "9723639994662424461853010022649271820444857466140000345187842923755820046429",
+                        ],
+                        [
+                            "1546025500589852331653781260294328997341323003933499403635891944384999648079",
+                            "8306453546668219599435057698602446343739223601535123245200478655802177419797",
+                        ],
+                    ),
+                ),
+                (
+                    b"abc",
+                    g2_point(
+                        [
+                            "10189838926952029149643861996045492066410863996718085633456972742940529306478",
+                            "4305203141213659409748410748229968669038079362376138746113410094684672203692",
+                        ],
+                        [
+                            "650602701383412647667071580991508468007178508160355207288763182977453139026",
+                            "11802782161085856101594712107602772520918705811693744242187021428189997829714",
+                        ],
+                    ),
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_bn446_g2_suites() {
+        check_suite::<Bn446G2Parameters>(
+            true,
+            &[
+                (
+                    b"",
+                    g2_point(
+                        [
+                            "90709901785162212006016465198942427454946598312848576082954880812427205167377788317336093938982017941414980611027908800607287761090382",
+                            "70082188025810005137212347884461773105593712461614514110503642972463633657442397291408541443604288754983576390687053913839929997171966",
+                        ],
+                        [
+                            "30359943449189220176091678531461958115735185639099352233246793053097245372519368585478355614566966348246971584042949100419068697804954",
+                            "31457230508899312838107080257731942679301094250587344851158085978945407909539228680020114902569700901512254459595904756564620512454412",
+                        ],
+                    ),
+                ),
+                (
+                    b"abc",
+                    g2_point(
+                        [
+                            "21722062262571452343990172086767259327760879071909627187415711231676890384901290682502751976685576135002510169083896163493717596336914",
+                            "44976846447461122081286491790521698619296277838417282923727509216755238210627467754679617177814297262799961414791695965352325641705307",
+                        ],
+                        [
+                            "9732660094879016743306073815249517575391504548649267171247782297298445314275929715173013695956497485390051015544078196340913960652901",
+                            "75821380897863317976098438854219015066717032666188885347874244934076418980949236342353124708444048563363604939612181505795257124792530",
+                        ],
+                    ),
+                ),
+            ],
+        );
+        check_suite::<Bn446G2Parameters>(
+            false,
+            &[
+                (
+                    b"",
+                    g2_point(
+                        [
+                            "11412145982564241601000862387742333204168963143885184372383497702138327444422591815061583890524738098280473124566006348029078713786205",
+                            "12797700115354200977043060617848589021010016623891987374099230038893897419863843130066755143610233390160679483033452411547155821272837",
+                        ],
+                        [
+                            "28394637062035819703657380598688727417154403974364815516744021206525805725146060343409594721357846060544140313775580124707324180454444",
+                            "66326345909132236960757720529427908238775670093217345572751374518020252174557491960991376632926332096772238394379753558837753812343743",
+                        ],
+                    ),
+                ),
+                (
+                    b"abc",
+                    g2_point(
+                        [
+                            "37939486636211770687827025552261505455827626368355380790215437182143110641220768993147860990226629044581829197428491642777000333726084",
+                            "85167919449739506482141171091823961555004896458581777995462363968129914893544269910906495572020289530757106886533765308041191664433505",
+                        ],
+                        [
+                            "79819734312384120389326235568968707984850063065644309377941343620089449065460243847094252190931328939488896647638620495266895332641433",
+                            "51765965010202547678874082127359383668714075253347271710457912256740709326493567431771769491678127321503107945807526210762789223629678",
+                        ],
+                    ),
+                ),
+            ],
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! The Shallue-van de Woestijne map, following
+//!     Andrew Shallue and Christiaan E. van de Woestijne, "Construction of rational points on elliptic curves
+//!     over finite fields", ANTS 2006,
+//! with the straight-line formulas of RFC 9380, Section 6.6.1 and Appendix F.1.
+//!
+//! The map works for any Weierstrass curve, in particular for the curves y^2 = x^3 + b of BN curves, over Fq and Fq2.
+//! This implementation branches on whether g(x1) and g(x2) are squares, so it is not constant-time.
+
+use crate::hash_to_curve::SignedField;
+use ark_ec::short_weierstrass_jacobian::GroupAffine;
+use ark_ec::SWModelParameters;
+use ark_ff::{Field, LegendreSymbol, One, SquareRootField, Zero};
+use ark_std::{marker::PhantomData, ops::Neg};
+
+pub struct SvdWMap<P: SWModelParameters>
+where
+    P::BaseField: SignedField,
+{
+    /// The constant Z, chosen as in RFC 9380, Appendix H.1.
+    pub z: P::BaseField,
+    /// g(Z)
+    pub c1: P::BaseField,
+    /// -Z / 2
+    pub c2: P::BaseField,
+    /// sqrt(-g(Z) (3 Z^2 + 4 A)), with sgn0(c3) = 0
+    pub c3: P::BaseField,
+    /// -4 g(Z) / (3 Z^2 + 4 A)
+    pub c4: P::BaseField,
+
+    #[doc(hidden)]
+    pub phantom: PhantomData<P>,
+}
+
+impl<P: SWModelParameters> Default for SvdWMap<P>
+where
+    P::BaseField: SignedField,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: SWModelParameters> SvdWMap<P>
+where
+    P::BaseField: SignedField,
+{
+    pub fn new() -> Self {
+        let z = Self::find_z();
+        let c1 = Self::g(&z);
+        let c2 = z.neg() * &P::BaseField::from(2u64).inverse().unwrap();
+
+        let t = Self::three_z_square_plus_four_a(&z);
+        let mut c3 = (c1 * &t).neg().sqrt().unwrap();
+        if c3.sgn0() {
+            c3 = c3.neg();
+        }
+        let c4 = (c1 * &P::BaseField::from(4u64)).neg() * &t.inverse().unwrap();
+
+        Self {
+            z,
+            c1,
+            c2,
+            c3,
+            c4,
+            phantom: PhantomData,
+        }
+    }
+
+    /// g(x) = x^3 + A x + B
+    fn g(x: &P::BaseField) -> P::BaseField {
+        (x.square() + &P::COEFF_A) * x + &P::COEFF_B
+    }
+
+    fn three_z_square_plus_four_a(z: &P::BaseField) -> P::BaseField {
+        z.square() * &P::BaseField::from(3u64) + &(P::COEFF_A * &P::BaseField::from(4u64))
+    }
+
+    fn is_square(val: &P::BaseField) -> bool {
+        val.legendre() != LegendreSymbol::QuadraticNonResidue
+    }
+
+    /// `find_z_svdw` of RFC 9380, Appendix H.1, which tries 1, -1, 2, -2, ...
+    fn find_z() -> P::BaseField {
+        let inv_2 = P::BaseField::from(2u64).inverse().unwrap();
+
+        let mut ctr = 1u64;
+        loop {
+            for z in [P::BaseField::from(ctr), P::BaseField::from(ctr).neg()] {
+                let gz = Self::g(&z);
+                let t = Self::three_z_square_plus_four_a(&z);
+                if gz.is_zero() || t.is_zero() {
+                    continue;
+                }
+                // h(Z) = -(3 Z^2 + 4 A) / (4 g(Z)) must be a non-zero square
+                let hz = t.neg() * &(gz * &P::BaseField::from(4u64)).inverse().unwrap();
+                if !Self::is_square(&hz) {
+                    continue;
+                }
+                if Self::is_square(&gz) || Self::is_square(&Self::g(&(z.neg() * &inv_2))) {
+                    return z;
+                }
+            }
+            ctr += 1;
+        }
+    }
+
+    /// Map a field element to a point of the curve, which is not necessarily in the prime-order subgroup.
+    pub fn map_to_curve(&self, u: P::BaseField) -> GroupAffine<P> {
+        let tv1 = u.square() * &self.c1;
+        let tv2 = P::BaseField::one() + &tv1;
+        let tv1 = P::BaseField::one() - &tv1;
+
+        // inv0, which maps 0 to 0
+        let tv3 = (tv1 * &tv2).inverse().unwrap_or_else(P::BaseField::zero);
+        let tv4 = u * &tv1 * &tv3 * &self.c3;
+
+        let x1 = self.c2 - &tv4;
+        let x2 = self.c2 + &tv4;
+        let x3 = (tv2.square() * &tv3).square() * &self.c4 + &self.z;
+
+        let x = if Self::is_square(&Self::g(&x1)) {
+            x1
+        } else if Self::is_square(&Self::g(&x2)) {
+            x2
+        } else {
+            x3
+        };
+
+        let mut y = Self::g(&x).sqrt().unwrap();
+        if u.sgn0() != y.sgn0() {
+            y = y.neg();
+        }
+
+        let point = GroupAffine::<P>::new(x, y, false);
+        debug_assert!(point.is_on_curve());
+
+        point
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::curve_bn254::{
+        g1::Parameters as Bn254G1Parameters, g2::Parameters as Bn254G2Parameters, Fq as Bn254Fq,
+        Fq2 as Bn254Fq2,
+    };
+    use crate::curve_bn446::{
+        g1::Parameters as Bn446G1Parameters, g2::Parameters as Bn446G2Parameters, Fq as Bn446Fq,
+        Fq2 as Bn446Fq2,
+    };
+    use crate::hash_to_curve::svdw::SvdWMap;
+    use crate::hash_to_curve::SignedField;
+    use ark_ec::SWModelParameters;
+    use ark_ff::{Field, One, SquareRootField, Zero};
+    use ark_std::UniformRand;
+
+    const REPETITIONS: u64 = 50;
+
+    fn check_svdw_map<P: SWModelParameters>(z: P::BaseField)
+    where
+        P::BaseField: SignedField,
+    {
+        let mut rng = ark_std::test_rng();
+        let map = SvdWMap::<P>::new();
+        assert_eq!(map.z, z);
+        assert!(!map.c3.sgn0());
+
+        for _ in 0..REPETITIONS {
+            let u = P::BaseField::rand(&mut rng);
+            let p = map.map_to_curve(u);
+            assert!(p.is_on_curve());
+            assert_eq!(p.y.sgn0(), u.sgn0());
+        }
+
+        // the exceptional inputs, where inv0 maps 0 to 0
+        assert!(map.map_to_curve(P::BaseField::zero()).is_on_curve());
+        if let Some(u) = map.c1.inverse().unwrap().sqrt() {
+            assert!(map.map_to_curve(u).is_on_curve());
+        }
+    }
+
+    #[test]
+    fn test_svdw_map() {
+        check_svdw_map::<Bn254G1Parameters>(-Bn254Fq::one());
+        check_svdw_map::<Bn446G1Parameters>(Bn446Fq::one());
+        check_svdw_map::<Bn254G2Parameters>(-Bn254Fq2::one());
+        check_svdw_map::<Bn446G2Parameters>(Bn446Fq2::one());
+    }
+}
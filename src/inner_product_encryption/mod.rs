@@ -0,0 +1,203 @@
+use crate::elgamal_encryption::{
+    ElGamalCiphertext, ElGamalPublicKey, ElGamalPublicParameters, ElGamalSecretKey,
+};
+use ark_ec::ProjectiveCurve;
+use ark_ff::{PrimeField, Zero};
+use ark_std::{collections::BTreeMap, marker::PhantomData, vec::Vec, UniformRand};
+
+/// The functional key for a weight vector `w`, with `sk_w = <x, w>`.
+#[derive(Clone)]
+pub struct InnerProductFunctionalKey<G: ProjectiveCurve> {
+    pub w: Vec<G::ScalarField>,
+    pub sk_w: G::ScalarField,
+}
+
+/// A baby-step giant-step table that recovers `v` from `g * v` for `0 <= v < bound`.
+///
+/// The table holds about `sqrt(bound)` points and can be reused across decryptions.
+pub struct DiscreteLogTable<G: ProjectiveCurve> {
+    pub bound: u64,
+    pub step: u64,
+    /// The negation of `g * step`, added once per giant step.
+    pub giant_step: G,
+    pub baby_steps: BTreeMap<Vec<u8>, u64>,
+}
+
+impl<G: ProjectiveCurve> DiscreteLogTable<G> {
+    pub fn new(pp: &ElGamalPublicParameters<G>, bound: u64) -> Self {
+        let mut step = 1u64;
+        while step.saturating_mul(step) < bound {
+            step += 1;
+        }
+
+        let mut points = Vec::<G>::new();
+        let mut cur = G::zero();
+        for _ in 0..step {
+            points.push(cur);
+            cur += &pp.g;
+        }
+        let giant_step = -cur;
+
+        G::batch_normalization(&mut points);
+
+        let mut baby_steps = BTreeMap::new();
+        for (j, point) in points.iter().enumerate() {
+            baby_steps.insert(Self::key(point), j as u64);
+        }
+
+        Self {
+            bound,
+            step,
+            giant_step,
+            baby_steps,
+        }
+    }
+
+    pub fn solve(&self, target: &G) -> Option<u64> {
+        let mut cur = *target;
+        let mut offset = 0u64;
+
+        while offset < self.bound {
+            if let Some(j) = self.baby_steps.get(&Self::key(&cur)) {
+                let v = offset + j;
+                return if v < self.bound { Some(v) } else { None };
+            }
+            cur += &self.giant_step;
+            offset += self.step;
+        }
+
+        None
+    }
+
+    fn key(point: &G) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        point.serialize(&mut bytes).unwrap();
+        bytes
+    }
+}
+
+/// DDH-based inner-product functional encryption, following Abdalla, Bourse, De Caro, and Pointcheval,
+/// on top of the ElGamal key pairs.
+///
+/// A ciphertext of `m` is an ElGamal ciphertext of the points `g * m_i`,
+/// and the functional key for `w` only reveals `g * <m, w>`.
+pub struct InnerProductEncryption<G: ProjectiveCurve> {
+    pub pairing_engine_phantom: PhantomData<G>,
+}
+
+impl<G: ProjectiveCurve> InnerProductEncryption<G> {
+    pub fn encrypt<R: ark_std::rand::Rng>(
+        pk: &ElGamalPublicKey<G>,
+        plaintext: &[G::ScalarField],
+        rng: &mut R,
+    ) -> ElGamalCiphertext<G> {
+        assert!(plaintext.len() <= pk.y.len());
+
+        let scalar_r = G::ScalarField::rand(rng);
+        let r = pk.pp.g.mul(scalar_r.into_repr());
+
+        let mut e = Vec::<G>::new();
+
+        for (m, y) in plaintext.iter().zip(pk.y.iter()) {
+            e.push(pk.pp.g.mul(m.into_repr()) + y.mul(scalar_r.into_repr()));
+        }
+        ElGamalCiphertext::<G> { r, e }
+    }
+
+    pub fn key_derivation(
+        sk: &ElGamalSecretKey<G>,
+        w: &[G::ScalarField],
+    ) -> InnerProductFunctionalKey<G> {
+        assert!(w.len() <= sk.scalar_x.len());
+
+        let mut sk_w = G::ScalarField::zero();
+        for (x, w) in sk.scalar_x.iter().zip(w.iter()) {
+            sk_w += &(*x * w);
+        }
+
+        InnerProductFunctionalKey::<G> {
+            w: w.to_vec(),
+            sk_w,
+        }
+    }
+
+    /// Compute `g * <m, w>` from a ciphertext of `m`.
+    pub fn decrypt(fk: &InnerProductFunctionalKey<G>, ciphertext: &ElGamalCiphertext<G>) -> G {
+        assert_eq!(fk.w.len(), ciphertext.e.len());
+
+        let mut res = -ciphertext.r.mul(fk.sk_w.into_repr());
+        for (e, w) in ciphertext.e.iter().zip(fk.w.iter()) {
+            res += &e.mul(w.into_repr());
+        }
+
+        res
+    }
+
+    /// Compute `<m, w>` from a ciphertext of `m`, if it lies in the range of the table.
+    pub fn decrypt_bounded(
+        table: &DiscreteLogTable<G>,
+        fk: &InnerProductFunctionalKey<G>,
+        ciphertext: &ElGamalCiphertext<G>,
+    ) -> Option<u64> {
+        table.solve(&Self::decrypt(fk, ciphertext))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::elgamal_encryption::ElGamalEncryption;
+    use crate::inner_product_encryption::{DiscreteLogTable, InnerProductEncryption};
+    use ark_bls12_381::{Fr, G1Projective};
+    use ark_ec::ProjectiveCurve;
+    use ark_ff::PrimeField;
+    use ark_std::rand::Rng;
+
+    #[test]
+    fn test_inner_product() {
+        let mut rng = ark_std::test_rng();
+        let len = 10;
+
+        let pp = ElGamalEncryption::<G1Projective>::setup(&mut rng);
+        let (sk, pk) = ElGamalEncryption::<G1Projective>::key_generation(&pp, len, &mut rng);
+
+        let mut m = Vec::new();
+        let mut w = Vec::new();
+        let mut expected = 0u64;
+        for _ in 0..len {
+            let m_i = rng.gen_range(0..1000u64);
+            let w_i = rng.gen_range(0..100u64);
+            m.push(Fr::from(m_i));
+            w.push(Fr::from(w_i));
+            expected += m_i * w_i;
+        }
+
+        let ct = InnerProductEncryption::encrypt(&pk, &m, &mut rng);
+        let fk = InnerProductEncryption::key_derivation(&sk, &w);
+
+        assert_eq!(
+            InnerProductEncryption::decrypt(&fk, &ct),
+            pp.g.mul(Fr::from(expected).into_repr())
+        );
+
+        let table = DiscreteLogTable::new(&pp, 1000 * 100 * len as u64);
+        assert_eq!(
+            InnerProductEncryption::decrypt_bounded(&table, &fk, &ct),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn test_discrete_log_bound() {
+        let mut rng = ark_std::test_rng();
+
+        let pp = ElGamalEncryption::<G1Projective>::setup(&mut rng);
+        let table = DiscreteLogTable::new(&pp, 1000);
+
+        for v in [0u64, 1, 31, 32, 500, 999].iter() {
+            assert_eq!(table.solve(&pp.g.mul(Fr::from(*v).into_repr())), Some(*v));
+        }
+        assert_eq!(table.solve(&pp.g.mul(Fr::from(1000u64).into_repr())), None);
+        assert_eq!(table.solve(&pp.g.mul(Fr::from(5000u64).into_repr())), None);
+        assert_eq!(table.solve(&-pp.g), None);
+    }
+}
@@ -3,11 +3,35 @@
 pub mod curve_bn254;
 pub mod curve_bn446;
 
+// The modules below predate the current clippy lints, and are kept as they are.
+#[allow(unused_variables, clippy::type_complexity, clippy::clone_on_copy)]
 pub mod endomorphisms;
 
+#[allow(
+    clippy::ptr_arg,
+    clippy::needless_range_loop,
+    clippy::needless_borrows_for_generic_args
+)]
 pub mod elgamal_encryption;
+#[allow(
+    clippy::ptr_arg,
+    clippy::needless_range_loop,
+    clippy::needless_borrows_for_generic_args
+)]
 pub mod shacham_encryption;
+pub mod signed_elgamal_encryption;
+pub mod updatable_encryption;
+
+pub mod ecies;
+pub mod inner_product_encryption;
 
 pub mod message_encoding;
 
+#[allow(unused_variables, clippy::clone_on_copy)]
 pub mod group_hashing;
+
+pub mod wahby_boneh_map;
+
+pub mod hash_to_curve;
+
+pub mod transcript;
@@ -0,0 +1,248 @@
+//! Branch-free helpers for the encoding over generic prime fields.
+//!
+//! The field elements of arkworks do not implement the `subtle` traits, so the selections here are
+//! arithmetic, and the comparisons are done with `subtle` over the limbs of the canonical representation.
+//! The helpers only guarantee that they do not branch on the values themselves. The field arithmetic
+//! of arkworks makes no timing guarantee, e.g., the Montgomery reduction ends with a conditional
+//! subtraction, so nothing built on them is claimed to be constant time end to end.
+
+use ark_ff::{BigInteger, Field, FpParameters, PrimeField};
+use ark_std::rand::RngCore;
+use ark_std::vec::Vec;
+use subtle::{Choice, ConstantTimeEq, ConstantTimeLess};
+
+/// The window size of [`pow_fixed_window`].
+const WINDOW_BITS: usize = 4;
+
+/// Convert a choice into the field element zero or one.
+pub fn choice_to_field<F: PrimeField>(c: Choice) -> F {
+    // `from_repr` special-cases zero, so go through the nonzero values 1 and 2 instead
+    let repr = F::BigInt::from(1u64 + c.unwrap_u8() as u64);
+    F::from_repr(repr).unwrap() - &F::one()
+}
+
+/// Return `a` if `c` is false, and `b` if `c` is true.
+pub fn conditional_select<F: PrimeField>(a: &F, b: &F, c: Choice) -> F {
+    *a + &((*b - a) * &choice_to_field::<F>(c))
+}
+
+/// Compare two big integers of the same length, without branching on the limbs.
+pub fn ct_lt<B: BigInteger>(a: &B, b: &B) -> Choice {
+    let mut lt = Choice::from(0u8);
+    let mut eq = Choice::from(1u8);
+
+    for (x, y) in a.as_ref().iter().zip(b.as_ref().iter()).rev() {
+        lt |= eq & x.ct_lt(y);
+        eq &= x.ct_eq(y);
+    }
+
+    lt
+}
+
+/// Check if two field elements are equal, without branching on their limbs.
+pub fn ct_eq<F: PrimeField>(a: &F, b: &F) -> Choice {
+    let a = a.into_repr();
+    let b = b.into_repr();
+
+    let mut eq = Choice::from(1u8);
+    for (x, y) in a.as_ref().iter().zip(b.as_ref().iter()) {
+        eq &= x.ct_eq(y);
+    }
+
+    eq
+}
+
+/// Check if `a < (q - 1) / 2`, which is the sign convention of the encoding.
+pub fn is_positive<F: PrimeField>(a: &F) -> Choice {
+    ct_lt(&a.into_repr(), &F::Params::MODULUS_MINUS_ONE_DIV_TWO)
+}
+
+/// Compute `base^exp` for a public exponent with a fixed window.
+///
+/// Unlike `Field::pow`, every window costs the same squarings and one multiplication,
+/// so the sequence of operations only depends on the length of the exponent.
+pub fn pow_fixed_window<F: Field>(base: F, exp: &[u64]) -> F {
+    let mut table = [F::one(); 1 << WINDOW_BITS];
+    for i in 1..table.len() {
+        table[i] = table[i - 1] * &base;
+    }
+
+    let mut res = F::one();
+    for limb in exp.iter().rev() {
+        for i in (0..64 / WINDOW_BITS).rev() {
+            for _ in 0..WINDOW_BITS {
+                res.square_in_place();
+            }
+            // the exponent is public, so is the index
+            let digit = (limb >> (i * WINDOW_BITS)) as usize & ((1 << WINDOW_BITS) - 1);
+            res *= &table[digit];
+        }
+    }
+
+    res
+}
+
+/// Compute a square root of a square `a` with the branch-free Tonelli-Shanks of RFC 9380, Appendix I.4,
+/// for q - 1 = 2^s t with t odd, given (t - 1) / 2 and a primitive 2^s-th root of unity.
+///
+/// The loops only depend on s, so the sequence of operations is the same for all inputs.
+/// The result is undefined if `a` is not a square.
+pub fn tonelli_shanks<F: PrimeField>(
+    a: F,
+    two_adicity: u32,
+    t_minus_one_div_two: &[u64],
+    root_of_unity: F,
+) -> F {
+    let mut z = pow_fixed_window(a, t_minus_one_div_two);
+    let mut t = z.square() * &a;
+    z *= &a;
+    let mut b = t;
+    let mut c = root_of_unity;
+
+    for k in (2..=two_adicity).rev() {
+        for _ in 0..k - 2 {
+            b.square_in_place();
+        }
+        let e = ct_eq(&b, &F::one());
+
+        z = conditional_select(&(z * &c), &z, e);
+        c.square_in_place();
+        t = conditional_select(&(t * &c), &t, e);
+        b = t;
+    }
+
+    z
+}
+
+/// Invert `a`, blinded by a random factor, so that the variable-time inversion
+/// only sees a uniformly random element. Returns `None` if `a` is zero.
+pub fn blinded_inverse<F: Field, R: RngCore>(a: F, rng: &mut R) -> Option<F> {
+    let mut blind = F::rand(rng);
+    while blind.is_zero() {
+        blind = F::rand(rng);
+    }
+
+    (a * &blind).inverse().map(|inv| inv * &blind)
+}
+
+/// Invert `a` as [`blinded_inverse`] does, but return zero if `a` is zero, without branching on it.
+pub fn blinded_inverse_or_zero<F: PrimeField, R: RngCore>(a: F, rng: &mut R) -> F {
+    let is_zero = ct_eq(&a, &F::zero());
+    let inv = blinded_inverse(conditional_select(&a, &F::one(), is_zero), rng).unwrap();
+    conditional_select(&inv, &F::zero(), is_zero)
+}
+
+/// Invert all the values in place with Montgomery's trick, sharing a single [`blinded_inverse`],
+/// and map zeros to zero as [`blinded_inverse_or_zero`] does, without branching on them.
+pub fn blinded_batch_inverse_or_zero<F: PrimeField, R: RngCore>(values: &mut [F], rng: &mut R) {
+    let is_zero: Vec<Choice> = values.iter().map(|a| ct_eq(a, &F::zero())).collect();
+
+    // the prefix products, with the zeros replaced by one
+    let mut prefix = Vec::with_capacity(values.len());
+    let mut acc = F::one();
+    for (a, z) in values.iter_mut().zip(is_zero.iter()) {
+        *a = conditional_select(a, &F::one(), *z);
+        prefix.push(acc);
+        acc *= &*a;
+    }
+
+    let mut inv = blinded_inverse(acc, rng).unwrap();
+    for ((a, prefix), z) in values.iter_mut().zip(prefix).zip(is_zero).rev() {
+        let a_inv = inv * &prefix;
+        inv *= &*a;
+        *a = conditional_select(&a_inv, &F::zero(), z);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::curve_bn254::Fr as Bn254Fr;
+    use crate::curve_bn446::{Fq as Bn446Fq, Fr as Bn446Fr};
+    use crate::message_encoding::constant_time::{
+        blinded_batch_inverse_or_zero, blinded_inverse, blinded_inverse_or_zero,
+        conditional_select, ct_eq, ct_lt, is_positive, pow_fixed_window, tonelli_shanks,
+    };
+    use ark_ff::{FftParameters, Field, FpParameters, PrimeField, Zero};
+    use ark_std::rand::RngCore;
+    use ark_std::UniformRand;
+    use subtle::Choice;
+
+    const REPETITIONS: u64 = 100;
+
+    #[test]
+    fn test_constant_time_helpers() {
+        let mut rng = ark_std::test_rng();
+        let half = <Bn446Fq as PrimeField>::Params::MODULUS_MINUS_ONE_DIV_TWO;
+
+        for _ in 0..REPETITIONS {
+            let a = Bn446Fq::rand(&mut rng);
+            let b = Bn446Fq::rand(&mut rng);
+
+            assert_eq!(conditional_select(&a, &b, Choice::from(0u8)), a);
+            assert_eq!(conditional_select(&a, &b, Choice::from(1u8)), b);
+
+            assert_eq!(bool::from(ct_lt(&a.into_repr(), &b.into_repr())), a < b);
+            assert!(!bool::from(ct_lt(&a.into_repr(), &a.into_repr())));
+            assert_eq!(bool::from(is_positive(&a)), a.into_repr() < half);
+
+            assert!(bool::from(ct_eq(&a, &a)));
+            assert!(!bool::from(ct_eq(&a, &(a + &b))) || b.is_zero());
+
+            let exp = [rng.next_u64(), rng.next_u64()];
+            assert_eq!(pow_fixed_window(a, &exp), a.pow(exp));
+
+            assert_eq!(blinded_inverse(a, &mut rng), a.inverse());
+            assert_eq!(blinded_inverse_or_zero(a, &mut rng), a.inverse().unwrap());
+        }
+
+        assert_eq!(blinded_inverse(Bn446Fq::zero(), &mut rng), None);
+        assert_eq!(
+            blinded_inverse_or_zero(Bn446Fq::zero(), &mut rng),
+            Bn446Fq::zero()
+        );
+
+        let mut values: Vec<Bn446Fq> = (0..REPETITIONS).map(|_| Bn446Fq::rand(&mut rng)).collect();
+        values[0] = Bn446Fq::zero();
+        values[REPETITIONS as usize / 2] = Bn446Fq::zero();
+        let expected: Vec<Bn446Fq> = values
+            .iter()
+            .map(|a| a.inverse().unwrap_or_else(Bn446Fq::zero))
+            .collect();
+        blinded_batch_inverse_or_zero(&mut values, &mut rng);
+        assert_eq!(values, expected);
+
+        let mut empty: Vec<Bn446Fq> = Vec::new();
+        blinded_batch_inverse_or_zero(&mut empty, &mut rng);
+        assert!(empty.is_empty());
+    }
+
+    fn check_tonelli_shanks<F: PrimeField>() {
+        let mut rng = ark_std::test_rng();
+
+        let two_adicity = F::Params::TWO_ADICITY;
+        let t_minus_one_div_two = F::Params::T_MINUS_ONE_DIV_TWO;
+        let root_of_unity = F::two_adic_root_of_unity();
+
+        for _ in 0..REPETITIONS {
+            let a = F::rand(&mut rng).square();
+            let sqrt = tonelli_shanks(a, two_adicity, t_minus_one_div_two.as_ref(), root_of_unity);
+            assert_eq!(sqrt.square(), a);
+        }
+
+        let zero = tonelli_shanks(
+            F::zero(),
+            two_adicity,
+            t_minus_one_div_two.as_ref(),
+            root_of_unity,
+        );
+        assert!(zero.is_zero());
+    }
+
+    #[test]
+    fn test_tonelli_shanks() {
+        // the two-adicities are 2, 5, and 32
+        check_tonelli_shanks::<Bn254Fr>();
+        check_tonelli_shanks::<Bn446Fr>();
+        check_tonelli_shanks::<ark_bls12_381::Fr>();
+    }
+}
@@ -0,0 +1,157 @@
+use crate::message_encoding::{Encoder, EncoderError};
+use ark_ec::short_weierstrass_jacobian::GroupAffine;
+use ark_ec::{AffineCurve, ProjectiveCurve, SWModelParameters};
+use ark_ff::PrimeField;
+use ark_std::rand::{Rng, RngCore};
+use ark_std::{vec::Vec, UniformRand};
+
+/// Elligator Squared, following Mehdi Tibouchi, "Elligator Squared: Uniform Points on Elliptic Curves
+/// of Prime Order as Uniform Random Strings", FC 2014.
+///
+/// A point `P` is represented by a pair `(u, v)` with `f(u) + f(v) = P`, where `f` is the
+/// Fouque-Tibouchi map of [`Encoder`]. For a uniformly random point, the representation is
+/// statistically close to a uniformly random pair of field elements.
+pub struct ElligatorSquared<P: SWModelParameters>
+where
+    P::BaseField: PrimeField,
+{
+    pub encoder: Encoder<P>,
+}
+
+impl<P: SWModelParameters> ElligatorSquared<P>
+where
+    P::BaseField: PrimeField,
+{
+    pub fn new() -> Result<Self, EncoderError> {
+        Ok(Self {
+            encoder: Encoder::<P>::new()?,
+        })
+    }
+
+    /// Sample a representation of the point, uniformly among all its representations.
+    pub fn encode<R: RngCore>(
+        &self,
+        p: &GroupAffine<P>,
+        rng: &mut R,
+    ) -> (P::BaseField, P::BaseField) {
+        loop {
+            let u = P::BaseField::rand(rng);
+            let (f_u, _) = self.encoder.encode(u, rng);
+
+            let q = (p.into_projective() - &f_u.into_projective()).into_affine();
+            let preimages = self.preimages(&q, rng);
+
+            // accept with probability #preimages / 4, and then pick a uniformly random preimage
+            let k = rng.gen_range(0..4);
+            if k < preimages.len() {
+                return (u, preimages[k]);
+            }
+        }
+    }
+
+    /// Compute the point represented by the pair.
+    pub fn decode<R: RngCore>(
+        &self,
+        repr: &(P::BaseField, P::BaseField),
+        rng: &mut R,
+    ) -> GroupAffine<P> {
+        let (f_u, _) = self.encoder.encode(repr.0, rng);
+        let (f_v, _) = self.encoder.encode(repr.1, rng);
+
+        (f_u.into_projective() + &f_v.into_projective()).into_affine()
+    }
+
+    /// Compute all the preimages of the point under the map, of which there are at most four.
+    pub fn preimages<R: RngCore>(&self, p: &GroupAffine<P>, rng: &mut R) -> Vec<P::BaseField> {
+        let mut preimages = Vec::new();
+
+        // the candidates of the hints are only preimages if the point is encoded via the same case
+        for candidate in self.encoder.decode_without_hints(*p).iter().flatten() {
+            if preimages.contains(candidate) {
+                continue;
+            }
+            if self.encoder.encode(*candidate, rng).0 == *p {
+                preimages.push(*candidate);
+            }
+        }
+
+        preimages
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::curve_bn254::g1::Parameters as Bn254G1Parameters;
+    use crate::curve_bn446::g1::Parameters as Bn446G1Parameters;
+    use crate::message_encoding::elligator_squared::ElligatorSquared;
+    use ark_ec::short_weierstrass_jacobian::{GroupAffine, GroupProjective};
+    use ark_ec::{AffineCurve, ModelParameters, ProjectiveCurve, SWModelParameters};
+    use ark_ff::PrimeField;
+    use ark_ff::Zero;
+    use ark_std::UniformRand;
+
+    const REPETITIONS: u64 = 20;
+
+    fn check_elligator_squared<P: SWModelParameters>()
+    where
+        P::BaseField: PrimeField,
+    {
+        let mut rng = ark_std::test_rng();
+        let elligator_squared = ElligatorSquared::<P>::new().unwrap();
+
+        let mut points = vec![
+            GroupAffine::<P>::zero(),
+            GroupAffine::<P>::prime_subgroup_generator(),
+        ];
+        for _ in 0..REPETITIONS {
+            points.push(GroupProjective::<P>::rand(&mut rng).into_affine());
+        }
+
+        for p in points.iter() {
+            let repr = elligator_squared.encode(p, &mut rng);
+            assert_eq!(elligator_squared.decode(&repr, &mut rng), *p);
+
+            // the representation is randomized
+            let repr_other = elligator_squared.encode(p, &mut rng);
+            assert_ne!(repr, repr_other);
+            assert_eq!(elligator_squared.decode(&repr_other, &mut rng), *p);
+        }
+
+        // the message encoding composes with Elligator Squared
+        for _ in 0..REPETITIONS {
+            let t = P::BaseField::rand(&mut rng);
+            let (p, h) = elligator_squared.encoder.encode(t, &mut rng);
+
+            let repr = elligator_squared.encode(&p, &mut rng);
+            let p_recovered = elligator_squared.decode(&repr, &mut rng);
+            assert_eq!(
+                elligator_squared
+                    .encoder
+                    .decode_with_hints(p_recovered, h)
+                    .unwrap(),
+                t
+            );
+        }
+    }
+
+    #[test]
+    fn test_elligator_squared() {
+        check_elligator_squared::<Bn254G1Parameters>();
+        check_elligator_squared::<Bn446G1Parameters>();
+    }
+
+    #[test]
+    fn test_preimages() {
+        let mut rng = ark_std::test_rng();
+        let elligator_squared = ElligatorSquared::<Bn446G1Parameters>::new().unwrap();
+
+        for _ in 0..REPETITIONS {
+            let t = <Bn446G1Parameters as ModelParameters>::BaseField::rand(&mut rng);
+            let (p, _) = elligator_squared.encoder.encode(t, &mut rng);
+
+            let preimages = elligator_squared.preimages(&p, &mut rng);
+            assert!(preimages.contains(&t));
+            assert!(preimages.len() <= 4);
+        }
+    }
+}
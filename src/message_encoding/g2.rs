@@ -0,0 +1,356 @@
+use crate::message_encoding::{jacobi, DecodeError, DecodeHint, EncoderError};
+use ark_ec::bn::{BnParameters, G2Affine};
+use ark_ec::SWModelParameters;
+use ark_ff::{Field, Fp2, FpParameters, LegendreSymbol, One, PrimeField, SquareRootField, Zero};
+use ark_std::cmp::Ordering;
+use ark_std::{ops::Neg, vec::Vec};
+
+type Fq2<P> = Fp2<<P as BnParameters>::Fp2Params>;
+
+/// The Fouque-Tibouchi encoding into the sextic twist y^2 = x^3 + b' over Fq2, with the same hints as
+/// [`crate::message_encoding::Encoder`].
+///
+/// The points are on the twist, but are in general not in the prime-order subgroup G2.
+/// Unlike the encoding into G1, this one is not constant-time.
+pub struct G2Encoder<P: BnParameters> {
+    pub b: Fq2<P>,
+    pub b_plus_one: Fq2<P>,
+
+    pub minus_one: Fq2<P>,
+    pub minus_3: Fq2<P>,
+    pub minus_4_times_b_plus_1: Fq2<P>,
+
+    pub inv_2: Fq2<P>,
+
+    pub sqrt_minus_3: Fq2<P>,
+    pub minus_sqrt_minus_3: Fq2<P>,
+    pub sqrt_minus_3_minus_1_div_2: Fq2<P>,
+    pub minus_sqrt_minus_3_div_2: Fq2<P>,
+
+    /// The exceptional inputs with their outputs, as in [`crate::message_encoding::Encoder`].
+    /// Every element of Fq is a square in Fq2, so 1 + b' and -(1 + b') are both squares or both non-squares.
+    pub exceptional_inputs: Vec<(Fq2<P>, G2Affine<P>, DecodeHint)>,
+}
+
+impl<P: BnParameters> G2Encoder<P> {
+    pub fn new() -> Result<Self, EncoderError> {
+        let b = P::G2Parameters::COEFF_B;
+        let b_plus_one = b + &Fq2::<P>::one();
+        if b.is_zero() || b_plus_one.is_zero() {
+            return Err(EncoderError::UnsupportedCoefficientB);
+        }
+
+        let minus_one = Fq2::<P>::one().neg();
+        let minus_3 = Fq2::<P>::from(3u64).neg();
+        let minus_4_times_b_plus_1 = Fq2::<P>::from(4u64).neg() * &b_plus_one;
+
+        let inv_2 = Fq2::<P>::from(2u64).inverse().unwrap();
+
+        // -3 is in Fq, and so it is always a square in Fq2
+        let sqrt_minus_3 = minus_3.sqrt().unwrap();
+        let minus_sqrt_minus_3 = sqrt_minus_3.neg();
+        let sqrt_minus_3_minus_1_div_2 = (minus_one + &sqrt_minus_3) * &inv_2;
+        let minus_sqrt_minus_3_div_2 = inv_2 * &minus_sqrt_minus_3;
+
+        let mut exceptional_inputs = Vec::new();
+        match b_plus_one.sqrt() {
+            Some(sqrt_b_plus_one) => exceptional_inputs.push((
+                Fq2::<P>::zero(),
+                G2Affine::<P>::new(
+                    sqrt_minus_3_minus_1_div_2,
+                    Self::positive(sqrt_b_plus_one),
+                    false,
+                ),
+                1,
+            )),
+            None => exceptional_inputs.push((Fq2::<P>::zero(), G2Affine::<P>::zero(), 1)),
+        }
+        if let Some(root) = b_plus_one.neg().sqrt() {
+            let root = Self::positive(root);
+            exceptional_inputs.push((root, G2Affine::<P>::zero(), 2));
+            exceptional_inputs.push((root.neg(), G2Affine::<P>::zero(), 3));
+        }
+
+        Ok(Self {
+            b,
+            b_plus_one,
+
+            minus_one,
+            minus_3,
+            minus_4_times_b_plus_1,
+
+            inv_2,
+
+            sqrt_minus_3,
+            minus_sqrt_minus_3,
+            sqrt_minus_3_minus_1_div_2,
+            minus_sqrt_minus_3_div_2,
+
+            exceptional_inputs,
+        })
+    }
+
+    /// The sign of an element of Fq2: the sign of c0 if c0 is nonzero, and the sign of c1 otherwise,
+    /// where an element of Fq is positive if it is less than (q - 1) / 2.
+    pub fn is_positive(val: &Fq2<P>) -> bool {
+        let half = <P::Fp as PrimeField>::Params::MODULUS_MINUS_ONE_DIV_TWO;
+        if !val.c0.is_zero() {
+            val.c0.into_repr().cmp(&half) == Ordering::Less
+        } else {
+            val.c1.into_repr().cmp(&half) == Ordering::Less
+        }
+    }
+
+    fn positive(val: Fq2<P>) -> Fq2<P> {
+        if Self::is_positive(&val) {
+            val
+        } else {
+            val.neg()
+        }
+    }
+
+    /// The quadratic character over Fq2, which is the Legendre symbol of the norm c0^2 - beta * c1^2 over Fq.
+    pub fn compute_legendre_symbol(&self, val: Fq2<P>) -> i32 {
+        let norm = val.norm();
+
+        jacobi::jacobi(&norm.into_repr(), &<P::Fp as PrimeField>::Params::MODULUS).unwrap_or_else(
+            || match norm.legendre() {
+                LegendreSymbol::Zero => 0,
+                LegendreSymbol::QuadraticResidue => 1,
+                LegendreSymbol::QuadraticNonResidue => -1,
+            },
+        )
+    }
+
+    /// Compute a square root of a square.
+    pub fn compute_square_root(&self, val: Fq2<P>) -> Fq2<P> {
+        val.sqrt().unwrap()
+    }
+
+    fn g(&self, x: Fq2<P>) -> Fq2<P> {
+        x.square() * &x + &self.b
+    }
+
+    pub fn encode(&self, val: Fq2<P>) -> (G2Affine<P>, DecodeHint) {
+        if let Some((_, point, hint)) = self
+            .exceptional_inputs
+            .iter()
+            .find(|(exceptional_val, _, _)| *exceptional_val == val)
+        {
+            return (*point, *hint);
+        }
+
+        // w is 1/u
+        let w =
+            self.minus_sqrt_minus_3 * &val * &(self.b_plus_one + &val.square()).inverse().unwrap();
+
+        let x1 = self.sqrt_minus_3_minus_1_div_2 + val * &w;
+        let x2 = self.minus_one - &x1;
+
+        let u = w.inverse().unwrap();
+        let x3 = Fq2::<P>::one() + &u.square();
+
+        let (x, idx) = if self.compute_legendre_symbol(self.g(x1)) == 1 {
+            (x1, 1)
+        } else if self.compute_legendre_symbol(self.g(x2)) == 1 {
+            (x2, 2)
+        } else {
+            (x3, 3)
+        };
+
+        let y = self.compute_square_root(self.g(x));
+
+        // the sign of y is the sign of val in the first two cases, and tells which root of
+        // val^2 + sqrt(-3) u val + 1 + b = 0 val is in the third case
+        let sgn_expected = if idx == 1 || idx == 2 {
+            Self::is_positive(&val)
+        } else {
+            let delta_sqrt =
+                self.compute_square_root(self.minus_3 * &u.square() + &self.minus_4_times_b_plus_1);
+            val == u * &self.minus_sqrt_minus_3_div_2 - &(delta_sqrt * &self.inv_2)
+        };
+        let y = if Self::is_positive(&y) == sgn_expected {
+            y
+        } else {
+            y.neg()
+        };
+
+        let point = G2Affine::<P>::new(x, y, false);
+        debug_assert!(point.is_on_curve());
+
+        let hint = if idx == 3 && !Self::is_positive(&u) {
+            4
+        } else {
+            idx
+        };
+
+        (point, hint)
+    }
+
+    fn helper_decode_attempt_1_2(&self, x: Fq2<P>, y: Fq2<P>) -> Option<Fq2<P>> {
+        let step_1 = (x - &self.sqrt_minus_3_minus_1_div_2).neg().inverse()?;
+        let step_2 = (step_1 * &self.sqrt_minus_3 + &self.minus_one).inverse()?;
+        let step_3 = step_2 * &self.b_plus_one;
+        if self.compute_legendre_symbol(step_3) == -1 {
+            None
+        } else {
+            let step_4 = self.compute_square_root(step_3);
+
+            if Self::is_positive(&step_4) == Self::is_positive(&y) {
+                Some(step_4)
+            } else {
+                Some(step_4.neg())
+            }
+        }
+    }
+
+    fn helper_decode_attempt_3_4(&self, u: Fq2<P>, y: Fq2<P>) -> Option<Fq2<P>> {
+        let step_1 = u.square() * &self.minus_3 + &self.minus_4_times_b_plus_1;
+
+        if self.compute_legendre_symbol(step_1) == -1 {
+            None
+        } else {
+            let mid = self.minus_sqrt_minus_3_div_2 * &u;
+            let step_2 = self.compute_square_root(step_1) * &self.inv_2;
+
+            if Self::is_positive(&y) {
+                Some(mid - &step_2)
+            } else {
+                Some(mid + &step_2)
+            }
+        }
+    }
+
+    /// Compare two points, ignoring the coordinates of the point at infinity.
+    fn is_same_point(p1: &G2Affine<P>, p2: &G2Affine<P>) -> bool {
+        p1.infinity == p2.infinity && (p1.infinity || (p1.x == p2.x && p1.y == p2.y))
+    }
+
+    fn find_exceptional_input(&self, p: &G2Affine<P>, hint: DecodeHint) -> Option<Fq2<P>> {
+        self.exceptional_inputs
+            .iter()
+            .find(|(_, point, exceptional_hint)| {
+                *exceptional_hint == hint && Self::is_same_point(point, p)
+            })
+            .map(|(val, _, _)| *val)
+    }
+
+    /// Decode the point with the hint, with u = +sqrt(x - 1) for hint 3 and u = -sqrt(x - 1) for hint 4.
+    fn decode_attempt(&self, p: &G2Affine<P>, hint: DecodeHint) -> Option<Fq2<P>> {
+        if let Some(val) = self.find_exceptional_input(p, hint) {
+            return Some(val);
+        }
+        if p.infinity {
+            return None;
+        }
+
+        match hint {
+            1 => self.helper_decode_attempt_1_2(p.x, p.y),
+            2 => self.helper_decode_attempt_1_2((p.x + &Fq2::<P>::one()).neg(), p.y),
+            _ => {
+                let x_minus_one = p.x - &Fq2::<P>::one();
+                if self.compute_legendre_symbol(x_minus_one) == -1 {
+                    return None;
+                }
+                let u = Self::positive(self.compute_square_root(x_minus_one));
+
+                if hint == 3 {
+                    self.helper_decode_attempt_3_4(u, p.y)
+                } else {
+                    self.helper_decode_attempt_3_4(u.neg(), p.y)
+                }
+            }
+        }
+    }
+
+    pub fn decode_without_hints(&self, p: G2Affine<P>) -> [Option<Fq2<P>>; 4] {
+        [
+            self.decode_attempt(&p, 1),
+            self.decode_attempt(&p, 2),
+            self.decode_attempt(&p, 3),
+            self.decode_attempt(&p, 4),
+        ]
+    }
+
+    pub fn decode_with_hints(&self, p: G2Affine<P>, hint: u8) -> Result<Fq2<P>, DecodeError> {
+        if !(1..=4).contains(&hint) {
+            return Err(DecodeError::InvalidHint(hint));
+        }
+        if !p.is_on_curve() {
+            return Err(DecodeError::PointNotOnCurve);
+        }
+
+        self.decode_attempt(&p, hint).ok_or(DecodeError::NoPreimage)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::curve_bn254::Parameters as Bn254Parameters;
+    use crate::curve_bn446::Parameters as Bn446Parameters;
+    use crate::message_encoding::g2::G2Encoder;
+    use crate::message_encoding::DecodeError;
+    use ark_ec::bn::{BnParameters, G2Affine};
+    use ark_ff::{Fp2, LegendreSymbol, One, SquareRootField, Zero};
+    use ark_std::UniformRand;
+
+    const REPETITIONS: u64 = 50;
+
+    fn check_g2_encoding<P: BnParameters>(num_exceptional_inputs: usize) {
+        let mut rng = ark_std::test_rng();
+        let encoder = G2Encoder::<P>::new().unwrap();
+
+        for _ in 0..REPETITIONS {
+            let a = Fp2::<P::Fp2Params>::rand(&mut rng);
+            let expected = match a.legendre() {
+                LegendreSymbol::Zero => 0,
+                LegendreSymbol::QuadraticResidue => 1,
+                LegendreSymbol::QuadraticNonResidue => -1,
+            };
+            assert_eq!(encoder.compute_legendre_symbol(a), expected);
+
+            let t = Fp2::<P::Fp2Params>::rand(&mut rng);
+            let (p, h) = encoder.encode(t);
+            assert!(p.is_on_curve());
+
+            assert_eq!(encoder.decode_with_hints(p, h), Ok(t));
+            assert_eq!(encoder.decode_without_hints(p)[h as usize - 1], Some(t));
+        }
+
+        // t = 0 and the roots of t^2 = -(1 + b')
+        let mut inputs = vec![Fp2::<P::Fp2Params>::zero()];
+        if let Some(root) = (-encoder.b_plus_one).sqrt() {
+            inputs.push(root);
+            inputs.push(-root);
+        }
+        assert_eq!(inputs.len(), num_exceptional_inputs);
+
+        for t in inputs.iter() {
+            let (p, h) = encoder.encode(*t);
+            assert!(p.is_on_curve());
+            assert_eq!(encoder.decode_with_hints(p, h), Ok(*t));
+        }
+
+        let (p, h) = encoder.encode(Fp2::<P::Fp2Params>::one());
+        assert_eq!(
+            encoder.decode_with_hints(p, 5),
+            Err(DecodeError::InvalidHint(5))
+        );
+        let mut p_corrupted = p;
+        p_corrupted.y += &Fp2::<P::Fp2Params>::one();
+        assert_eq!(
+            encoder.decode_with_hints(p_corrupted, h),
+            Err(DecodeError::PointNotOnCurve)
+        );
+        assert_eq!(
+            encoder.decode_with_hints(G2Affine::<P>::zero(), 4),
+            Err(DecodeError::NoPreimage)
+        );
+    }
+
+    #[test]
+    fn test_g2_encoding() {
+        check_g2_encoding::<Bn254Parameters>(1);
+        check_g2_encoding::<Bn446Parameters>(3);
+    }
+}
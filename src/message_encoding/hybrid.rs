@@ -1,98 +1,571 @@
-use crate::message_encoding::{DecodeHint, Encoder};
+use crate::message_encoding::{DecodeError, DecodeHint, Encoder};
 use ark_ec::bn::{BnParameters, G1Affine};
 use ark_ff::{BigInteger, PrimeField};
-use ark_std::rand::RngCore;
-use blake2::digest::{Update, VariableOutput};
-use blake2::VarBlake2s;
+use ark_std::fmt;
+use ark_std::rand::{RngCore, SeedableRng};
+use blake2::digest::{Digest, Update, VariableOutput};
+use blake2::{VarBlake2b, VarBlake2s};
+use rand_chacha::ChaChaRng;
+use sha2::Sha256;
 
 #[cfg(feature = "parallel")]
-use ark_std::{cfg_iter, cfg_iter_mut};
+use ark_std::cfg_iter;
 
 #[cfg(feature = "parallel")]
-use rayon::iter::{
-    IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator,
-};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
-// The length of the hash value in the hybrid embedding,
-// 80 is chosen heuristically treating the VarBlake2 as a random oracle.
+// The default length of the hash value in the hybrid embedding, in bytes,
+// 80 bits is chosen heuristically treating the VarBlake2 as a random oracle.
 //
 // Note that we are not using the collision resistance of the hash function,
 // we are using the pseudorandomness of the hash function, treated as the random oracle.
-const HASH_LEN: usize = 80;
+const DEFAULT_TAG_LEN: usize = 10;
+
+/// The padding marker after the message, which is followed by zeros up to the end of the last block.
+pub(crate) const PADDING_MARKER: u8 = 0x80;
+
+/// Append the padding after a message, which is 0x80 followed by the zeros up to a whole number of blocks.
+pub(crate) fn pad_message(bytes: &mut Vec<u8>, capacity: usize) {
+    bytes.push(PADDING_MARKER);
+    bytes.resize(bytes.len().div_ceil(capacity) * capacity, 0);
+}
+
+/// The length of the message before the padding, which must be 0x80 followed by zeros,
+/// starting in the last `capacity` bytes.
+pub(crate) fn unpadded_len(bytes: &[u8], capacity: usize) -> Result<usize, HybridDecodeError> {
+    bytes
+        .iter()
+        .rposition(|b| *b != 0)
+        .filter(|end| bytes[*end] == PADDING_MARKER && bytes.len() - end <= capacity)
+        .ok_or(HybridDecodeError::InvalidFraming)
+}
+
+/// The number of data points of a compact block is stored as a little-endian u16 in the hint point.
+const COMPACT_COUNT_BYTES: usize = 2;
+
+/// Read `num_bits` bits of the bytes from the bit offset, least significant bit first, into
+/// `num_bits.div_ceil(8)` bytes. The bits past the end of the bytes are zero.
+fn read_bits(bytes: &[u8], offset: usize, num_bits: usize) -> Vec<u8> {
+    let mut res = vec![0u8; num_bits.div_ceil(8)];
+    for i in 0..num_bits {
+        let pos = offset + i;
+        if pos / 8 < bytes.len() {
+            res[i / 8] |= ((bytes[pos / 8] >> (pos % 8)) & 1) << (i % 8);
+        }
+    }
+    res
+}
+
+/// Write the first `num_bits` bits of the source into the destination from the bit offset,
+/// where the destination bits must be zero.
+fn write_bits(dest: &mut [u8], offset: usize, src: &[u8], num_bits: usize) {
+    for i in 0..num_bits {
+        let pos = offset + i;
+        dest[pos / 8] |= ((src[i / 8] >> (i % 8)) & 1) << (pos % 8);
+    }
+}
+
+/// Whether the little-endian bytes are less than 2^num_bits.
+fn fits_in_bits(bytes: &[u8], num_bits: usize) -> bool {
+    let full_bytes = num_bits / 8;
+    if full_bytes >= bytes.len() {
+        return true;
+    }
+    (bytes[full_bytes] >> (num_bits % 8)) == 0 && bytes[full_bytes + 1..].iter().all(|b| *b == 0)
+}
+
+/// The errors of decoding corrupted blocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HybridDecodeError {
+    /// The number of points is not a whole number of blocks.
+    WrongLength { expected: usize, found: usize },
+    /// The point at the index is not on the curve.
+    PointNotOnCurve(usize),
+    /// No preimage of the hint point has a matching tag, so the hint point is missing or tampered.
+    HintTagMismatch,
+    /// More than one preimage of the hint point has a matching tag.
+    AmbiguousHints,
+    /// The data point at the index does not decode with its hint.
+    InvalidDataPoint(usize, DecodeError),
+    /// The data point at the index decodes to a value with more than `num_bits_per_point` bits.
+    DataOutOfRange(usize),
+    /// The length or the padding of the message is not well-formed.
+    InvalidFraming,
+    /// The hint point of a compact block runs out of hint bits at the data point at the index.
+    MissingHints(usize),
+}
+
+impl fmt::Display for HybridDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HybridDecodeError::WrongLength { expected, found } => {
+                write!(f, "expected {} points, found {}", expected, found)
+            }
+            HybridDecodeError::PointNotOnCurve(index) => {
+                write!(f, "the point {} is not on the curve", index)
+            }
+            HybridDecodeError::HintTagMismatch => {
+                write!(f, "the hint point is missing or tampered")
+            }
+            HybridDecodeError::AmbiguousHints => {
+                write!(
+                    f,
+                    "more than one candidate of the hint point matches the tag"
+                )
+            }
+            HybridDecodeError::InvalidDataPoint(index, err) => {
+                write!(f, "the data point {} does not decode: {}", index, err)
+            }
+            HybridDecodeError::DataOutOfRange(index) => {
+                write!(
+                    f,
+                    "the data point {} decodes to a value out of range",
+                    index
+                )
+            }
+            HybridDecodeError::InvalidFraming => {
+                write!(f, "the length or the padding of the message is malformed")
+            }
+            HybridDecodeError::MissingHints(index) => {
+                write!(
+                    f,
+                    "the hint point has no hints left for the data point {}",
+                    index
+                )
+            }
+        }
+    }
+}
+
+impl ark_std::error::Error for HybridDecodeError {}
+
+/// The hash function of the tag of the hints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TagHash {
+    Blake2s,
+    Blake2b,
+    Sha256,
+}
+
+impl TagHash {
+    /// The maximal tag length in bytes, which is the output length of the hash function.
+    pub fn max_tag_len(&self) -> usize {
+        match self {
+            TagHash::Blake2s | TagHash::Sha256 => 32,
+            TagHash::Blake2b => 64,
+        }
+    }
+
+    /// The tag of the hints, which is H(hints) without a domain separator, and
+    /// H(len(domain) || domain || hints) with one. The Blake2 variants output `tag_len` bytes,
+    /// and SHA-256 is truncated to `tag_len` bytes.
+    pub fn tag(&self, domain_separator: Option<&[u8]>, hints: &[u8], tag_len: usize) -> Vec<u8> {
+        let mut input = Vec::new();
+        if let Some(domain_separator) = domain_separator {
+            input.extend_from_slice(&(domain_separator.len() as u64).to_le_bytes());
+            input.extend_from_slice(domain_separator);
+        }
+        input.extend_from_slice(hints);
+
+        let mut res = Vec::new();
+        match self {
+            TagHash::Blake2s => {
+                let mut hasher = VarBlake2s::new(tag_len).unwrap();
+                hasher.update(&input);
+                hasher.finalize_variable_reset(|r| res = r.to_vec());
+            }
+            TagHash::Blake2b => {
+                let mut hasher = VarBlake2b::new(tag_len).unwrap();
+                hasher.update(&input);
+                hasher.finalize_variable_reset(|r| res = r.to_vec());
+            }
+            TagHash::Sha256 => {
+                res = Sha256::digest(&input)[..tag_len].to_vec();
+            }
+        }
+        res
+    }
+}
+
+/// The errors of building a hybrid encoder with unsupported parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HybridEncoderError {
+    /// The tag is empty.
+    EmptyTag,
+    /// The tag is longer than the output of the hash function.
+    TagTooLong { tag_len: usize, max_tag_len: usize },
+    /// The number of data points is zero.
+    NoDataPoints,
+    /// The hints and the tag do not fit in the hint point.
+    HintsDoNotFit {
+        num_data_points: usize,
+        max_num_data_points: usize,
+    },
+}
+
+impl fmt::Display for HybridEncoderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HybridEncoderError::EmptyTag => write!(f, "the tag is empty"),
+            HybridEncoderError::TagTooLong {
+                tag_len,
+                max_tag_len,
+            } => write!(
+                f,
+                "the tag of {} bytes is longer than the hash output of {} bytes",
+                tag_len, max_tag_len
+            ),
+            HybridEncoderError::NoDataPoints => write!(f, "the number of data points is zero"),
+            HybridEncoderError::HintsDoNotFit {
+                num_data_points,
+                max_num_data_points,
+            } => write!(
+                f,
+                "the hints of {} data points do not fit in the hint point, which has room for {}",
+                num_data_points, max_num_data_points
+            ),
+        }
+    }
+}
+
+impl ark_std::error::Error for HybridEncoderError {}
+
+/// The errors of the compact encoding on inputs that it cannot encode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompactEncodeError {
+    /// The length of the input is not a multiple of `num_bits_per_point` bits.
+    WrongLength {
+        num_bytes: usize,
+        num_bits_per_point: usize,
+    },
+    /// The tag leaves fewer than 2 bits for the hints in the hint point.
+    NoRoomForHints { num_hint_bits: usize },
+}
+
+impl fmt::Display for CompactEncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompactEncodeError::WrongLength {
+                num_bytes,
+                num_bits_per_point,
+            } => write!(
+                f,
+                "the input of {} bytes is not a multiple of {} bits",
+                num_bytes, num_bits_per_point
+            ),
+            CompactEncodeError::NoRoomForHints { num_hint_bits } => write!(
+                f,
+                "the tag leaves {} bits for the hints in the hint point, fewer than 2",
+                num_hint_bits
+            ),
+        }
+    }
+}
+
+impl ark_std::error::Error for CompactEncodeError {}
+
+/// The builder of [`HybridEncoder`], which defaults to an 80-bit Blake2s tag without a domain separator,
+/// and to as many data points as the hint point has room for.
+pub struct HybridEncoderBuilder<P: BnParameters + Sync> {
+    encoder: Option<Encoder<P::G1Parameters>>,
+    tag_len: usize,
+    tag_hash: TagHash,
+    domain_separator: Option<Vec<u8>>,
+    num_data_points: Option<usize>,
+    bit_packing: bool,
+}
+
+impl<P: BnParameters + Sync> Default for HybridEncoderBuilder<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: BnParameters + Sync> HybridEncoderBuilder<P> {
+    pub fn new() -> Self {
+        Self {
+            encoder: None,
+            tag_len: DEFAULT_TAG_LEN,
+            tag_hash: TagHash::Blake2s,
+            domain_separator: None,
+            num_data_points: None,
+            bit_packing: false,
+        }
+    }
+
+    /// Use a precomputed encoder, such as [`crate::message_encoding::bn254_encoder`].
+    pub fn encoder(mut self, encoder: Encoder<P::G1Parameters>) -> Self {
+        self.encoder = Some(encoder);
+        self
+    }
+
+    /// The length of the tag, in bytes.
+    pub fn tag_len(mut self, tag_len: usize) -> Self {
+        self.tag_len = tag_len;
+        self
+    }
+
+    pub fn tag_hash(mut self, tag_hash: TagHash) -> Self {
+        self.tag_hash = tag_hash;
+        self
+    }
+
+    /// A domain separator mixed into the tag, such as a per-file nonce, so that the blocks of
+    /// one file do not decode under the parameters of another.
+    pub fn domain_separator(mut self, domain_separator: &[u8]) -> Self {
+        self.domain_separator = Some(domain_separator.to_vec());
+        self
+    }
+
+    /// The number of data points in a block, which is at most the number of hints that fit in the hint point.
+    pub fn num_data_points(mut self, num_data_points: usize) -> Self {
+        self.num_data_points = Some(num_data_points);
+        self
+    }
+
+    /// Pack the bytes at the bit level, so that every data point carries the full capacity of the field,
+    /// and the hints take 2 bits each in the hint point, followed directly by the tag. The default packs
+    /// whole bytes, which loses up to 7 bits per point, but is the format of the existing blocks.
+    pub fn bit_packing(mut self, bit_packing: bool) -> Self {
+        self.bit_packing = bit_packing;
+        self
+    }
+
+    pub fn build(self) -> Result<HybridEncoder<P>, HybridEncoderError> {
+        if self.tag_len == 0 {
+            return Err(HybridEncoderError::EmptyTag);
+        }
+        if self.tag_len > self.tag_hash.max_tag_len() {
+            return Err(HybridEncoderError::TagTooLong {
+                tag_len: self.tag_len,
+                max_tag_len: self.tag_hash.max_tag_len(),
+            });
+        }
+
+        let capacity = P::Fp::size_in_bits() - 1;
+        let num_bytes_per_point = capacity >> 3; // divide the capacity directly by 8
+
+        // every data point takes 2 bits of the hint point, next to the tag, and the hints fill
+        // whole bytes without the bit packing
+        let (num_bits_per_point, max_num_data_points) = if self.bit_packing {
+            (capacity, capacity.saturating_sub(self.tag_len * 8) / 2)
+        } else {
+            (
+                num_bytes_per_point * 8,
+                num_bytes_per_point.saturating_sub(self.tag_len) * 4,
+            )
+        };
+        let num_data_points = self.num_data_points.unwrap_or(max_num_data_points);
+        if num_data_points == 0 {
+            return Err(HybridEncoderError::NoDataPoints);
+        }
+        if num_data_points > max_num_data_points {
+            return Err(HybridEncoderError::HintsDoNotFit {
+                num_data_points,
+                max_num_data_points,
+            });
+        }
+
+        let encoder = match self.encoder {
+            Some(encoder) => encoder,
+            None => Encoder::<P::G1Parameters>::new().unwrap(),
+        };
+
+        Ok(HybridEncoder {
+            num_bytes_per_point,
+            num_bits_per_point,
+            bit_packing: self.bit_packing,
+            num_data_points,
+            tag_len: self.tag_len,
+            tag_hash: self.tag_hash,
+            domain_separator: self.domain_separator,
+            encoder,
+        })
+    }
+}
+
+/// The density of the compact encoding of some bytes, compared to the blocks of [`HybridEncoder::encode`].
+///
+/// The compact encoding is denser because its tag covers only the hint point, not the data, so the
+/// numbers compare an unauthenticated encoding against the fixed blocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DensityReport {
+    pub num_bytes: usize,
+    pub num_data_points: usize,
+    /// The number of data points with more than one candidate, which are the only ones that need hint bits.
+    pub num_ambiguous: usize,
+    /// The number of points of the compact encoding, including the hint points.
+    pub num_points: usize,
+    /// The number of bytes and the number of points of a fixed block.
+    pub fixed_capacity: usize,
+    pub fixed_block_len: usize,
+}
+
+impl DensityReport {
+    pub fn fixed_bytes_per_point(&self) -> f64 {
+        self.fixed_capacity as f64 / self.fixed_block_len as f64
+    }
+
+    pub fn compact_bytes_per_point(&self) -> f64 {
+        self.num_bytes as f64 / self.num_points as f64
+    }
+
+    /// The relative gain in density of the compact encoding over the fixed blocks.
+    pub fn gain(&self) -> f64 {
+        self.compact_bytes_per_point() / self.fixed_bytes_per_point() - 1.0
+    }
+}
+
+impl fmt::Display for DensityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} data points ambiguous, {} points, {:.3} bytes per point (fixed: {:.3}), gain {:.2}%",
+            self.num_ambiguous,
+            self.num_data_points,
+            self.num_points,
+            self.compact_bytes_per_point(),
+            self.fixed_bytes_per_point(),
+            self.gain() * 100.0
+        )
+    }
+}
 
 pub struct HybridEncoder<P: BnParameters + Sync> {
     // The number of bytes for an embed-direct point
     pub num_bytes_per_point: usize,
+    // The number of bits for an embed-direct point, which is the capacity of the field with the bit packing
+    pub num_bits_per_point: usize,
+    pub bit_packing: bool,
     // The number of data points in a group
     pub num_data_points: usize,
-    pub encoder: Encoder<P>,
+    // The length of the tag of the hints, in bytes
+    pub tag_len: usize,
+    pub tag_hash: TagHash,
+    pub domain_separator: Option<Vec<u8>>,
+    pub encoder: Encoder<P::G1Parameters>,
 }
 
-unsafe impl<P: BnParameters + Sync> Sync for HybridEncoder<P> {}
+impl<P: BnParameters + Sync> Default for HybridEncoder<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl<P: BnParameters + Sync> HybridEncoder<P> {
-    pub unsafe fn new() -> Self {
-        let capacity = P::Fp::size_in_bits() - 1;
-        let num_bytes_per_point = capacity >> 3; // divide the capacity directly by 8
+    pub fn new() -> Self {
+        Self::from_encoder(Encoder::<P::G1Parameters>::new().unwrap())
+    }
 
-        assert!(num_bytes_per_point > 10);
-        let num_points = (num_bytes_per_point * 8 - HASH_LEN) / 2;
+    /// Construct the hybrid encoder from a precomputed encoder, such as [`crate::message_encoding::bn254_encoder`].
+    pub fn from_encoder(encoder: Encoder<P::G1Parameters>) -> Self {
+        Self::builder().encoder(encoder).build().unwrap()
+    }
 
-        Self {
-            num_bytes_per_point: num_bytes_per_point,
-            num_data_points: num_points,
-            encoder: Encoder::<P>::new(),
+    pub fn builder() -> HybridEncoderBuilder<P> {
+        HybridEncoderBuilder::new()
+    }
+
+    /// The number of bits of the hints in the hint point, with 2 bits per data point,
+    /// which are rounded up to whole bytes without the bit packing.
+    fn num_hint_bits(&self) -> usize {
+        if self.bit_packing {
+            self.num_data_points * 2
+        } else {
+            self.num_data_points.div_ceil(4) * 8
         }
     }
 
+    fn tag(&self, hints: &[u8]) -> Vec<u8> {
+        self.tag_hash
+            .tag(self.domain_separator.as_deref(), hints, self.tag_len)
+    }
+
     pub fn get_capacity(&self) -> usize {
-        // return how many bytes the hybrid encoder will encode in one pass
-        return self.num_bytes_per_point * self.num_data_points;
+        // return how many bytes the hybrid encoder will encode in one pass, where the last data point
+        // is padded with zero bits if the bits of the data points are not whole bytes
+        self.num_bits_per_point * self.num_data_points / 8
     }
 
-    pub fn encode<R: RngCore>(&self, bytes: &[u8], rng: &mut R) -> Vec<G1Affine<P>> {
-        assert_eq!(bytes.len(), self.get_capacity());
+    /// The stream of the point with the given index, where the index of the hint point is `num_data_points`.
+    fn point_rng(seed: &<ChaChaRng as SeedableRng>::Seed, index: u64) -> ChaChaRng {
+        let mut rng = ChaChaRng::from_seed(*seed);
+        rng.set_stream(index);
+        rng
+    }
 
-        // split and encode them to self.num_points points
-        // store the hints
-        let mut points = Vec::<G1Affine<P>>::new();
-        let mut hints = Vec::<DecodeHint>::new();
+    /// Split the bytes into `num_points` field elements of `num_bits_per_point` bits each, where the bits
+    /// past the end of the bytes are zero.
+    fn unpack_field_elements(&self, bytes: &[u8], num_points: usize) -> Vec<P::Fp> {
+        (0..num_points)
+            .map(|i| {
+                P::Fp::from_le_bytes_mod_order(&read_bits(
+                    bytes,
+                    i * self.num_bits_per_point,
+                    self.num_bits_per_point,
+                ))
+            })
+            .collect()
+    }
 
+    /// Encode the data points of the field elements, where the point with index i uses the stream i
+    /// under the `parallel` feature, and the batch uses the stream `batch_stream` otherwise.
+    fn encode_data_points(
+        &self,
+        field_elements: &[P::Fp],
+        seed: &<ChaChaRng as SeedableRng>::Seed,
+        batch_stream: u64,
+    ) -> Vec<(G1Affine<P>, DecodeHint)> {
         #[cfg(feature = "parallel")]
         {
-            points.resize(self.num_data_points, G1Affine::<P>::default());
-            hints.resize(self.num_data_points, 0);
-
-            let mut field_elements = Vec::<P::Fp>::new();
-            for i in 0..self.num_data_points {
-                field_elements.push(P::Fp::from_le_bytes_mod_order(
-                    &bytes[i * self.num_bytes_per_point..(i + 1) * self.num_bytes_per_point],
-                ));
-            }
-
+            let _ = batch_stream;
             cfg_iter!(field_elements)
-                .zip(cfg_iter_mut!(points))
-                .zip(cfg_iter_mut!(hints))
-                .for_each(|((f, p), h)| {
-                    // This would no longer use the RngCore to provide the randomness
-                    let (point, hint) = self.encoder.encode(*f, &mut rand::thread_rng());
-                    *p = point;
-                    *h = hint;
+                .enumerate()
+                .map(|(i, f)| {
+                    self.encoder
+                        .encode(*f, &mut Self::point_rng(seed, i as u64))
                 })
+                .collect()
         }
 
         #[cfg(not(feature = "parallel"))]
         {
-            for i in 0..self.num_data_points {
-                // convert the bytes to a field element using P::Fp::from_le_bytes_mod_order
-                let field_element = P::Fp::from_le_bytes_mod_order(
-                    &bytes[i * self.num_bytes_per_point..(i + 1) * self.num_bytes_per_point],
-                );
-                let (point, hint) = self.encoder.encode(field_element, rng);
-                points.push(point);
-                hints.push(hint);
-            }
+            self.encode_data_points_sequential(field_elements, seed, batch_stream)
         }
+    }
+
+    /// The sequential path of [`Self::encode_data_points`], which the tests of the `parallel` feature
+    /// compare against.
+    #[cfg(any(not(feature = "parallel"), test))]
+    fn encode_data_points_sequential(
+        &self,
+        field_elements: &[P::Fp],
+        seed: &<ChaChaRng as SeedableRng>::Seed,
+        batch_stream: u64,
+    ) -> Vec<(G1Affine<P>, DecodeHint)> {
+        let mut batch_rng = Self::point_rng(seed, batch_stream);
+        self.encoder.encode_batch(field_elements, &mut batch_rng)
+    }
+
+    /// Encode a block of [`Self::get_capacity`] bytes into `num_data_points` points and the hint point.
+    ///
+    /// The randomness only masks the computation, so the points depend only on the bytes, and the
+    /// caller's rng is advanced in the same way with or without the `parallel` feature.
+    pub fn encode<R: RngCore>(&self, bytes: &[u8], rng: &mut R) -> Vec<G1Affine<P>> {
+        assert_eq!(bytes.len(), self.get_capacity());
+
+        // the masking randomness of every point comes from its own stream, seeded from the caller's rng,
+        // so the parallel and sequential builds draw the same 32 bytes from the caller's rng
+        let mut seed = <ChaChaRng as SeedableRng>::Seed::default();
+        rng.fill_bytes(&mut seed);
+
+        // split and encode them to self.num_points points
+        // store the hints
+        let field_elements = self.unpack_field_elements(bytes, self.num_data_points);
+        let (mut points, hints): (Vec<G1Affine<P>>, Vec<DecodeHint>) = self
+            .encode_data_points(&field_elements, &seed, self.num_data_points as u64 + 1)
+            .into_iter()
+            .unzip();
 
         let mut hints_bool: Vec<u8> = Vec::new();
         let mut hints_u8: Vec<u8> = Vec::new();
@@ -118,7 +591,7 @@ impl<P: BnParameters + Sync> HybridEncoder<P> {
             }
         }
 
-        hints_bool.chunks_exact(8).for_each(|bits| {
+        hints_bool.chunks(8).for_each(|bits| {
             let mut num: u8 = 0;
             for bit in bits.iter().rev() {
                 num *= 2;
@@ -127,53 +600,51 @@ impl<P: BnParameters + Sync> HybridEncoder<P> {
             hints_u8.push(num);
         });
 
-        // compute H(hints) and take the first 80-bit, which would be 10 bytes
-        let mut hasher = VarBlake2s::new(10).unwrap();
-        hasher.update(hints_u8.clone());
-
-        let mut res = Vec::new();
-        hasher.finalize_variable_reset(|r| res = r.to_vec());
+        // compute the tag H(hints), which has tag_len bytes
+        let tag = self.tag(&hints_u8);
 
-        hints_u8.extend(res.clone());
-
-        // encode the hints || H(hints)
-        let field_element = P::Fp::from_le_bytes_mod_order(&hints_u8);
-        let (point, _) = self.encoder.encode(field_element, rng);
+        // encode the hints || H(hints), with the tag right after the hint bits
+        let num_hint_bits = self.num_hint_bits();
+        let mut payload = vec![0u8; (num_hint_bits + self.tag_len * 8).div_ceil(8)];
+        write_bits(&mut payload, 0, &hints_u8, num_hint_bits);
+        write_bits(&mut payload, num_hint_bits, &tag, self.tag_len * 8);
+        let field_element = P::Fp::from_le_bytes_mod_order(&payload);
+        let (point, _) = self.encoder.encode(
+            field_element,
+            &mut Self::point_rng(&seed, self.num_data_points as u64),
+        );
 
         points.push(point);
         points
     }
 
-    pub fn decode(&self, points: &[G1Affine<P>]) -> Vec<u8> {
-        // take the last point out
-        let last_point = points.last().copied().unwrap();
-
-        // decode the last point
-        let decoded_last_point = self.encoder.decode_without_hints(last_point);
-
-        // check the candidate numbers, see which one matches the pattern, this involves a check of the H(.)
-        let mut hints_raw = Vec::new();
+    /// The hints of the candidate preimages of the hint point whose tag matches, which must be unique.
+    fn decode_hint_point(
+        &self,
+        hint_point: G1Affine<P>,
+    ) -> Result<Vec<DecodeHint>, HybridDecodeError> {
+        let num_hint_bits = self.num_hint_bits();
+        let num_tag_bits = self.tag_len * 8;
 
-        for candidate in decoded_last_point.iter() {
-            if let Some(field_element) = candidate {
-                let hints = field_element.into_repr().to_bytes_le();
-                let index = self.num_bytes_per_point - (HASH_LEN / 8);
-                let hashed = hints.get(index..index + (HASH_LEN / 8)).unwrap();
-                let values = hints.get(..index).unwrap();
-                let mut hasher = VarBlake2s::new(10).unwrap();
-                hasher.update(values);
+        let candidates = self.encoder.decode_without_hints(hint_point);
+        let mut matches = candidates.iter().flatten().filter_map(|field_element| {
+            // check the candidate numbers, see which one matches the pattern, this involves a check of the H(.)
+            let bytes = field_element.into_repr().to_bytes_le();
+            if !fits_in_bits(&bytes, num_hint_bits + num_tag_bits) {
+                return None;
+            }
+            let values = read_bits(&bytes, 0, num_hint_bits);
+            let hashed = read_bits(&bytes, num_hint_bits, num_tag_bits);
 
-                let mut res = Vec::new();
-                hasher.finalize_variable_reset(|r| res = r.to_vec());
+            (self.tag(&values) == hashed).then_some(values)
+        });
 
-                if res == hashed {
-                    hints_raw = values.to_vec();
-                }
-            }
+        let hints_raw = matches.next().ok_or(HybridDecodeError::HintTagMismatch)?;
+        if matches.next().is_some() {
+            return Err(HybridDecodeError::AmbiguousHints);
         }
-        assert!(!hints_raw.is_empty());
 
-        let mut hints: Vec<u8> = Vec::new();
+        let mut hints: Vec<DecodeHint> = Vec::new();
         for num in hints_raw.iter() {
             let n1: u8 = (num & 0b00000011) + 1;
             let n2: u8 = ((num & 0b00001100) >> 2) + 1;
@@ -184,49 +655,428 @@ impl<P: BnParameters + Sync> HybridEncoder<P> {
             hints.push(n3);
             hints.push(n4);
         }
+        hints.truncate(self.num_data_points);
 
-        let mut ret: Vec<u8> = Vec::new();
+        Ok(hints)
+    }
+
+    /// The bytes of a decoded data point, which must fit in `num_bits_per_point` bits.
+    fn data_point_bytes(
+        &self,
+        index: usize,
+        val: Result<P::Fp, DecodeError>,
+    ) -> Result<Vec<u8>, HybridDecodeError> {
+        let val = val.map_err(|err| HybridDecodeError::InvalidDataPoint(index, err))?;
+
+        let mut bytes = val.into_repr().to_bytes_le();
+        if !fits_in_bits(&bytes, self.num_bits_per_point) {
+            return Err(HybridDecodeError::DataOutOfRange(index));
+        }
+        bytes.truncate(self.num_bits_per_point.div_ceil(8));
+        Ok(bytes)
+    }
+
+    /// Concatenate the bits of the data points, `num_bits_per_point` bits each.
+    fn pack_data_points(&self, data: &[Vec<u8>]) -> Vec<u8> {
+        let mut ret = vec![0u8; (data.len() * self.num_bits_per_point).div_ceil(8)];
+        for (i, bytes) in data.iter().enumerate() {
+            write_bits(
+                &mut ret,
+                i * self.num_bits_per_point,
+                bytes,
+                self.num_bits_per_point,
+            );
+        }
+        ret
+    }
+
+    /// Decode a block from [`HybridEncoder::encode`], or report how the block is corrupted.
+    pub fn decode(&self, points: &[G1Affine<P>]) -> Result<Vec<u8>, HybridDecodeError> {
+        if points.len() != self.get_block_len() {
+            return Err(HybridDecodeError::WrongLength {
+                expected: self.get_block_len(),
+                found: points.len(),
+            });
+        }
+        if let Some(index) = points.iter().position(|p| !p.is_on_curve()) {
+            return Err(HybridDecodeError::PointNotOnCurve(index));
+        }
+
+        // take the last point out, and decode the hints from it
+        let (hint_point, data_points) = points.split_last().unwrap();
+        let hints = self.decode_hint_point(*hint_point)?;
+
+        let mut data: Vec<Vec<u8>> = Vec::with_capacity(self.num_data_points);
 
         #[cfg(feature = "parallel")]
         {
-            let partial_ret = cfg_iter!(points)
+            let partial_ret = cfg_iter!(data_points)
                 .zip(cfg_iter!(hints))
-                .map(|(p, h)| {
-                    self.encoder
-                        .decode_with_hints(*p, *h)
-                        .into_repr()
-                        .to_bytes_le()[..self.num_bytes_per_point]
-                        .to_vec()
-                })
+                .enumerate()
+                .map(|(i, (p, h))| self.data_point_bytes(i, self.encoder.decode_with_hints(*p, *h)))
                 .collect::<Vec<_>>();
 
-            for v in partial_ret.iter() {
-                ret.extend_from_slice(v);
+            for v in partial_ret {
+                data.push(v?);
             }
         }
 
         #[cfg(not(feature = "parallel"))]
         {
-            for (i, point) in points.iter().take(self.num_data_points).enumerate() {
-                ret.extend(
-                    &self
-                        .encoder
-                        .decode_with_hints(*point, hints[i])
-                        .into_repr()
-                        .to_bytes_le()[..self.num_bytes_per_point],
-                )
+            let inputs: Vec<_> = data_points
+                .iter()
+                .copied()
+                .zip(hints.iter().copied())
+                .collect();
+            for (i, val) in self.encoder.decode_batch(&inputs).into_iter().enumerate() {
+                data.push(self.data_point_bytes(i, val)?);
             }
         }
 
-        ret
+        // the padding bits of the last data point past the capacity must be zero
+        let mut ret = self.pack_data_points(&data);
+        if !fits_in_bits(&ret, self.get_capacity() * 8) {
+            return Err(HybridDecodeError::DataOutOfRange(self.num_data_points - 1));
+        }
+        ret.truncate(self.get_capacity());
+
+        Ok(ret)
+    }
+
+    /// The number of points of a block, which is the data points and the hint point.
+    pub fn get_block_len(&self) -> usize {
+        self.num_data_points + 1
+    }
+
+    /// Encode a message of any length, including the empty message, into a sequence of blocks.
+    ///
+    /// The message is framed as message || 0x80 || 0x00 ... 0x00, where the zeros fill the last block
+    /// to the capacity, so the length does not need to be known up front, as in
+    /// [`crate::message_encoding::stream::StreamEncoder`]. The framing is not an authentication:
+    /// dropping the last blocks is only detected when the new last block does not end like a padding.
+    pub fn encode_message<R: RngCore>(&self, message: &[u8], rng: &mut R) -> Vec<G1Affine<P>> {
+        let capacity = self.get_capacity();
+
+        let mut bytes = Vec::with_capacity(message.len() + capacity);
+        bytes.extend_from_slice(message);
+        pad_message(&mut bytes, capacity);
+
+        let mut points = Vec::with_capacity(bytes.len() / capacity * self.get_block_len());
+        for block in bytes.chunks_exact(capacity) {
+            points.extend(self.encode(block, rng));
+        }
+        points
+    }
+
+    /// Decode a sequence of blocks from [`HybridEncoder::encode_message`].
+    pub fn decode_message(&self, points: &[G1Affine<P>]) -> Result<Vec<u8>, HybridDecodeError> {
+        let block_len = self.get_block_len();
+        if points.is_empty() || !points.len().is_multiple_of(block_len) {
+            return Err(HybridDecodeError::WrongLength {
+                expected: points.len().div_ceil(block_len).max(1) * block_len,
+                found: points.len(),
+            });
+        }
+
+        let mut bytes = Vec::with_capacity(points.len() / block_len * self.get_capacity());
+        for block in points.chunks_exact(block_len) {
+            bytes.extend(self.decode(block)?);
+        }
+
+        let len = unpadded_len(&bytes, self.get_capacity())?;
+        bytes.truncate(len);
+        Ok(bytes)
+    }
+
+    /// The number of hint bits of a compact hint point, which holds the number of data points,
+    /// the hint bits and the tag in `num_bits_per_point` bits.
+    pub fn num_compact_hint_bits(&self) -> usize {
+        self.num_bits_per_point
+            .saturating_sub((self.tag_len + COMPACT_COUNT_BYTES) * 8)
+    }
+
+    /// The number of hint bits of a data point with the number of candidates, which is the bits
+    /// of the index of its value among the candidates.
+    fn num_index_bits(num_candidates: usize) -> usize {
+        match num_candidates {
+            0 | 1 => 0,
+            2 => 1,
+            _ => 2,
+        }
+    }
+
+    /// The distinct preimages of the point that fit in `num_bits_per_point` bits, in the order of their hints.
+    /// A data point decodes to one of them, so only its index among them needs to be stored.
+    fn compact_candidates(&self, p: G1Affine<P>) -> Vec<P::Fp> {
+        let mut candidates: Vec<P::Fp> = Vec::with_capacity(4);
+        for val in self.encoder.decode_without_hints(p).iter().flatten() {
+            let bytes = val.into_repr().to_bytes_le();
+            if fits_in_bits(&bytes, self.num_bits_per_point) && !candidates.contains(val) {
+                candidates.push(*val);
+            }
+        }
+        candidates
+    }
+
+    fn compact_candidates_batch(&self, points: &[G1Affine<P>]) -> Vec<Vec<P::Fp>> {
+        #[cfg(feature = "parallel")]
+        {
+            cfg_iter!(points)
+                .map(|p| self.compact_candidates(*p))
+                .collect()
+        }
+
+        #[cfg(not(feature = "parallel"))]
+        {
+            points.iter().map(|p| self.compact_candidates(*p)).collect()
+        }
+    }
+
+    /// Encode the bytes, a multiple of `num_bits_per_point` bits long, into compact blocks, and return
+    /// the number of ambiguous data points.
+    fn encode_compact_inner<R: RngCore>(
+        &self,
+        bytes: &[u8],
+        rng: &mut R,
+    ) -> Result<(Vec<G1Affine<P>>, usize), CompactEncodeError> {
+        if !(bytes.len() * 8).is_multiple_of(self.num_bits_per_point) {
+            return Err(CompactEncodeError::WrongLength {
+                num_bytes: bytes.len(),
+                num_bits_per_point: self.num_bits_per_point,
+            });
+        }
+
+        let max_hint_bits = self.num_compact_hint_bits();
+        if max_hint_bits < 2 {
+            return Err(CompactEncodeError::NoRoomForHints {
+                num_hint_bits: max_hint_bits,
+            });
+        }
+
+        let mut seed = <ChaChaRng as SeedableRng>::Seed::default();
+        rng.fill_bytes(&mut seed);
+
+        // the data points use the streams from 0, the batch and the hint points use the streams from u64::MAX
+        let field_elements =
+            self.unpack_field_elements(bytes, bytes.len() * 8 / self.num_bits_per_point);
+        let data_points: Vec<G1Affine<P>> = self
+            .encode_data_points(&field_elements, &seed, u64::MAX)
+            .into_iter()
+            .map(|(p, _)| p)
+            .collect();
+        let candidates = self.compact_candidates_batch(&data_points);
+
+        // the index of every value among the candidates of its point, with its number of bits
+        let indices: Vec<(usize, usize)> = field_elements
+            .iter()
+            .zip(candidates.iter())
+            .map(|(val, candidates)| {
+                let index = candidates
+                    .iter()
+                    .position(|c| c == val)
+                    .expect("the encoded value is a candidate of its point");
+                (index, Self::num_index_bits(candidates.len()))
+            })
+            .collect();
+        let num_ambiguous = candidates.iter().filter(|c| c.len() > 1).count();
+
+        let mut points = Vec::with_capacity(data_points.len() + 1);
+        let mut start = 0;
+        let mut block_index = 0u64;
+        while start < data_points.len() || block_index == 0 {
+            // take as many data points as the hint bits allow
+            let mut end = start;
+            let mut hint_bits: Vec<u8> = Vec::new();
+            while end < data_points.len()
+                && end - start < u16::MAX as usize
+                && hint_bits.len() + indices[end].1 <= max_hint_bits
+            {
+                let (index, num_bits) = indices[end];
+                for bit in 0..num_bits {
+                    hint_bits.push(((index >> bit) & 1) as u8);
+                }
+                end += 1;
+            }
+
+            // the hint point is count || hint bits || zeros || H(count || hint bits || zeros)
+            let prefix_bits = COMPACT_COUNT_BYTES * 8 + max_hint_bits;
+            let mut prefix = vec![0u8; prefix_bits.div_ceil(8)];
+            prefix[..COMPACT_COUNT_BYTES].copy_from_slice(&((end - start) as u16).to_le_bytes());
+            for (i, bit) in hint_bits.iter().enumerate() {
+                let pos = COMPACT_COUNT_BYTES * 8 + i;
+                prefix[pos / 8] |= bit << (pos % 8);
+            }
+            let tag = self.tag(&prefix);
+
+            let mut payload = vec![0u8; (prefix_bits + self.tag_len * 8).div_ceil(8)];
+            write_bits(&mut payload, 0, &prefix, prefix_bits);
+            write_bits(&mut payload, prefix_bits, &tag, self.tag_len * 8);
+
+            let (hint_point, _) = self.encoder.encode(
+                P::Fp::from_le_bytes_mod_order(&payload),
+                &mut Self::point_rng(&seed, u64::MAX - 1 - block_index),
+            );
+            points.push(hint_point);
+            points.extend_from_slice(&data_points[start..end]);
+
+            start = end;
+            block_index += 1;
+        }
+
+        Ok((points, num_ambiguous))
+    }
+
+    /// Encode the bytes, a multiple of `num_bits_per_point` bits long, into compact blocks of a hint point
+    /// followed by a variable number of data points.
+    ///
+    /// Most data points have only one preimage that fits in `num_bits_per_point` bits, so the hint point
+    /// stores the index of the value among the candidates only for the ambiguous ones, in 1 or 2 bits,
+    /// and a block has as many data points as the hint bits allow. The length of the bytes is not stored.
+    ///
+    /// The encoding is unauthenticated: the tag covers the number of data points and the hint bits, but
+    /// not the data points, so a data point replaced by another one with a candidate for its hint bits
+    /// decodes to other bytes without an error. The data must be authenticated separately.
+    pub fn encode_compact<R: RngCore>(
+        &self,
+        bytes: &[u8],
+        rng: &mut R,
+    ) -> Result<Vec<G1Affine<P>>, CompactEncodeError> {
+        Ok(self.encode_compact_inner(bytes, rng)?.0)
+    }
+
+    /// The number of data points and the hint bits of a compact hint point, whose tag must match uniquely.
+    fn decode_compact_hint_point(
+        &self,
+        hint_point: G1Affine<P>,
+    ) -> Result<(usize, Vec<u8>), HybridDecodeError> {
+        let max_hint_bits = self.num_compact_hint_bits();
+        let prefix_bits = COMPACT_COUNT_BYTES * 8 + max_hint_bits;
+
+        let candidates = self.compact_candidates(hint_point);
+        let mut matches = candidates.iter().filter_map(|field_element| {
+            let bytes = field_element.into_repr().to_bytes_le();
+            let prefix = read_bits(&bytes, 0, prefix_bits);
+            let hashed = read_bits(&bytes, prefix_bits, self.tag_len * 8);
+            (self.tag(&prefix) == hashed).then_some(prefix)
+        });
+
+        let prefix = matches.next().ok_or(HybridDecodeError::HintTagMismatch)?;
+        if matches.next().is_some() {
+            return Err(HybridDecodeError::AmbiguousHints);
+        }
+
+        let num_data_points = u16::from_le_bytes([prefix[0], prefix[1]]) as usize;
+        Ok((
+            num_data_points,
+            read_bits(&prefix, COMPACT_COUNT_BYTES * 8, max_hint_bits),
+        ))
+    }
+
+    /// Decode the compact blocks from [`HybridEncoder::encode_compact`], or report how they are corrupted.
+    ///
+    /// This does not authenticate the data points, see [`HybridEncoder::encode_compact`]: a successful
+    /// decoding only means that the hint points are intact and every data point has a candidate.
+    pub fn decode_compact(&self, points: &[G1Affine<P>]) -> Result<Vec<u8>, HybridDecodeError> {
+        if points.is_empty() {
+            return Err(HybridDecodeError::WrongLength {
+                expected: 1,
+                found: 0,
+            });
+        }
+        if let Some(index) = points.iter().position(|p| !p.is_on_curve()) {
+            return Err(HybridDecodeError::PointNotOnCurve(index));
+        }
+
+        let mut data: Vec<Vec<u8>> = Vec::new();
+        let mut start = 0;
+        while start < points.len() {
+            let (num_data_points, hint_bytes) = self.decode_compact_hint_point(points[start])?;
+            let end = start + 1 + num_data_points;
+            if end > points.len() {
+                return Err(HybridDecodeError::WrongLength {
+                    expected: end,
+                    found: points.len(),
+                });
+            }
+
+            let data_points = &points[start + 1..end];
+            let candidates = self.compact_candidates_batch(data_points);
+
+            let mut bit_position = 0;
+            for (i, candidates) in candidates.iter().enumerate() {
+                let point_index = start + 1 + i;
+
+                let num_bits = Self::num_index_bits(candidates.len());
+                if bit_position + num_bits > hint_bytes.len() * 8 {
+                    return Err(HybridDecodeError::MissingHints(point_index));
+                }
+                let index = read_bits(&hint_bytes, bit_position, num_bits)
+                    .first()
+                    .map_or(0, |b| *b as usize);
+                bit_position += num_bits;
+
+                match candidates.get(index) {
+                    Some(val) => {
+                        let mut bytes = val.into_repr().to_bytes_le();
+                        bytes.truncate(self.num_bits_per_point.div_ceil(8));
+                        data.push(bytes);
+                    }
+                    None => {
+                        let has_preimage = self
+                            .encoder
+                            .decode_without_hints(data_points[i])
+                            .iter()
+                            .any(|val| val.is_some());
+                        return Err(if candidates.is_empty() && has_preimage {
+                            HybridDecodeError::DataOutOfRange(point_index)
+                        } else {
+                            HybridDecodeError::InvalidDataPoint(
+                                point_index,
+                                DecodeError::NoPreimage,
+                            )
+                        });
+                    }
+                }
+            }
+
+            start = end;
+        }
+
+        Ok(self.pack_data_points(&data))
+    }
+
+    /// Encode the bytes, a multiple of `num_bits_per_point` bits long, in the compact encoding, and report
+    /// its density against the fixed blocks.
+    pub fn measure_density<R: RngCore>(
+        &self,
+        bytes: &[u8],
+        rng: &mut R,
+    ) -> Result<DensityReport, CompactEncodeError> {
+        let (points, num_ambiguous) = self.encode_compact_inner(bytes, rng)?;
+
+        Ok(DensityReport {
+            num_bytes: bytes.len(),
+            num_data_points: bytes.len() * 8 / self.num_bits_per_point,
+            num_ambiguous,
+            num_points: points.len(),
+            fixed_capacity: self.get_capacity(),
+            fixed_block_len: self.get_block_len(),
+        })
     }
 }
 
 #[cfg(test)]
 mod test {
+    use crate::curve_bn254::{Fq, G1Projective, Parameters as Bn254Parameters};
     use crate::curve_bn446::Parameters as Bn446Parameters;
-    use crate::message_encoding::hybrid::HybridEncoder;
-    use ark_std::rand::RngCore;
+    use crate::message_encoding::hybrid::{
+        CompactEncodeError, HybridDecodeError, HybridEncoder, HybridEncoderError, TagHash,
+    };
+    use ark_ec::bn::G1Affine;
+    use ark_ff::{One, PrimeField};
+    use ark_std::rand::{RngCore, SeedableRng};
+    use ark_std::UniformRand;
+    use rand_chacha::ChaChaRng;
 
     const REPETITIONS: u64 = 10;
 
@@ -235,16 +1085,447 @@ mod test {
         let mut rng = ark_std::test_rng();
 
         for _ in 0..REPETITIONS {
-            let encoder = unsafe { HybridEncoder::<Bn446Parameters>::new() };
+            let encoder = HybridEncoder::<Bn446Parameters>::new();
             let num_bytes = encoder.get_capacity();
 
             let mut test_bytes = vec![0u8; num_bytes];
             rng.fill_bytes(&mut test_bytes[..]);
 
             let points = encoder.encode(&test_bytes, &mut rng);
-            let test_bytes_recovered = encoder.decode(&points);
+            let test_bytes_recovered = encoder.decode(&points).unwrap();
 
             assert_eq!(test_bytes, test_bytes_recovered);
         }
     }
+
+    #[test]
+    fn test_deterministic_encoding() {
+        let mut rng = ark_std::test_rng();
+        let encoder = HybridEncoder::<Bn254Parameters>::new();
+
+        let mut test_bytes = vec![0u8; encoder.get_capacity()];
+        rng.fill_bytes(&mut test_bytes[..]);
+
+        let mut rng_1 = ChaChaRng::seed_from_u64(1);
+        let mut rng_2 = ChaChaRng::seed_from_u64(1);
+        let points = encoder.encode(&test_bytes, &mut rng_1);
+        assert_eq!(points, encoder.encode(&test_bytes, &mut rng_2));
+
+        // the points agree with encoding every field element on its own, which is what both
+        // the parallel and the sequential builds compute
+        let expected: Vec<_> = test_bytes
+            .chunks_exact(encoder.num_bytes_per_point)
+            .map(|chunk| {
+                encoder
+                    .encoder
+                    .encode(Fq::from_le_bytes_mod_order(chunk), &mut rng)
+                    .0
+            })
+            .collect();
+        assert_eq!(points[..encoder.num_data_points], expected[..]);
+
+        // under the `parallel` feature, the per-point streams give the same data points and hints
+        // as the sequential batch path
+        let field_elements = encoder.unpack_field_elements(&test_bytes, encoder.num_data_points);
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        assert_eq!(
+            encoder.encode_data_points(&field_elements, &seed, u64::MAX),
+            encoder.encode_data_points_sequential(&field_elements, &seed, u64::MAX)
+        );
+
+        // only the seed is drawn from the caller's rng
+        let mut rng_3 = ChaChaRng::seed_from_u64(1);
+        rng_3.fill_bytes(&mut [0u8; 32]);
+        assert_eq!(rng_1.next_u64(), rng_3.next_u64());
+    }
+
+    #[test]
+    fn test_message_rand() {
+        let mut rng = ark_std::test_rng();
+        let encoder = HybridEncoder::<Bn254Parameters>::new();
+        let capacity = encoder.get_capacity();
+
+        // the lengths around the block boundaries, where the framing needs one more block
+        for len in [0, 1, capacity - 1, capacity, 2 * capacity + 3] {
+            let mut message = vec![0u8; len];
+            rng.fill_bytes(&mut message[..]);
+
+            let points = encoder.encode_message(&message, &mut rng);
+            let num_blocks = (len + 1).div_ceil(capacity);
+            assert_eq!(points.len(), num_blocks * encoder.get_block_len());
+
+            assert_eq!(encoder.decode_message(&points), Ok(message));
+        }
+
+        // a message that ends with the padding marker and zeros is not confused with the padding
+        let message = vec![0x80, 0, 0];
+        let points = encoder.encode_message(&message, &mut rng);
+        assert_eq!(encoder.decode_message(&points), Ok(message));
+
+        let block_len = encoder.get_block_len();
+        assert_eq!(
+            encoder.decode_message(&[]),
+            Err(HybridDecodeError::WrongLength {
+                expected: block_len,
+                found: 0
+            })
+        );
+        assert_eq!(
+            encoder.decode_message(&points[1..]),
+            Err(HybridDecodeError::WrongLength {
+                expected: block_len,
+                found: block_len - 1
+            })
+        );
+
+        // the padding must start in the last block
+        let mut message = vec![0u8; 2 * capacity];
+        message[0] = 0x80;
+        let padded = encoder.encode_message(&message, &mut rng);
+        assert_eq!(
+            encoder.decode_message(&padded[..2 * encoder.get_block_len()]),
+            Err(HybridDecodeError::InvalidFraming)
+        );
+
+        // the blocks of a plain encoding do not have a valid framing
+        let points = encoder.encode(&vec![0xffu8; capacity], &mut rng);
+        assert_eq!(
+            encoder.decode_message(&points),
+            Err(HybridDecodeError::InvalidFraming)
+        );
+    }
+
+    #[test]
+    fn test_builder() {
+        let mut rng = ark_std::test_rng();
+
+        let default = HybridEncoder::<Bn446Parameters>::new();
+        assert_eq!(default.tag_len, 10);
+        assert_eq!(default.tag_hash, TagHash::Blake2s);
+        assert_eq!(default.num_data_points, (55 - 10) * 4);
+
+        for (tag_hash, tag_len) in [
+            (TagHash::Blake2s, 16),
+            (TagHash::Blake2b, 12),
+            (TagHash::Sha256, 8),
+        ] {
+            let encoder = HybridEncoder::<Bn254Parameters>::builder()
+                .tag_hash(tag_hash)
+                .tag_len(tag_len)
+                .domain_separator(b"file-1")
+                .build()
+                .unwrap();
+            assert_eq!(encoder.num_data_points, (31 - tag_len) * 4);
+
+            let mut test_bytes = vec![0u8; encoder.get_capacity()];
+            rng.fill_bytes(&mut test_bytes[..]);
+            let points = encoder.encode(&test_bytes, &mut rng);
+            assert_eq!(encoder.decode(&points), Ok(test_bytes));
+
+            // the blocks do not decode under another domain separator, or without one
+            let other = HybridEncoder::<Bn254Parameters>::builder()
+                .tag_hash(tag_hash)
+                .tag_len(tag_len)
+                .domain_separator(b"file-2")
+                .build()
+                .unwrap();
+            assert_eq!(
+                other.decode(&points),
+                Err(HybridDecodeError::HintTagMismatch)
+            );
+            let other = HybridEncoder::<Bn254Parameters>::builder()
+                .tag_hash(tag_hash)
+                .tag_len(tag_len)
+                .build()
+                .unwrap();
+            assert_eq!(
+                other.decode(&points),
+                Err(HybridDecodeError::HintTagMismatch)
+            );
+        }
+
+        // a number of data points that does not fill the last byte of the hints
+        let encoder = HybridEncoder::<Bn254Parameters>::builder()
+            .num_data_points(7)
+            .build()
+            .unwrap();
+        let mut test_bytes = vec![0u8; encoder.get_capacity()];
+        rng.fill_bytes(&mut test_bytes[..]);
+        let points = encoder.encode(&test_bytes, &mut rng);
+        assert_eq!(points.len(), 8);
+        assert_eq!(encoder.decode(&points), Ok(test_bytes));
+
+        let build = |tag_hash, tag_len, num_data_points| {
+            HybridEncoder::<Bn254Parameters>::builder()
+                .tag_hash(tag_hash)
+                .tag_len(tag_len)
+                .num_data_points(num_data_points)
+                .build()
+                .err()
+        };
+        assert_eq!(
+            build(TagHash::Blake2s, 0, 1),
+            Some(HybridEncoderError::EmptyTag)
+        );
+        assert_eq!(
+            build(TagHash::Sha256, 33, 1),
+            Some(HybridEncoderError::TagTooLong {
+                tag_len: 33,
+                max_tag_len: 32
+            })
+        );
+        assert_eq!(
+            build(TagHash::Blake2s, 10, 0),
+            Some(HybridEncoderError::NoDataPoints)
+        );
+        assert_eq!(
+            build(TagHash::Blake2s, 10, 85),
+            Some(HybridEncoderError::HintsDoNotFit {
+                num_data_points: 85,
+                max_num_data_points: 84
+            })
+        );
+        // the tag of BN254 cannot fill the hint point, and leaves no room for the hints
+        assert_eq!(
+            build(TagHash::Blake2s, 31, 1),
+            Some(HybridEncoderError::HintsDoNotFit {
+                num_data_points: 1,
+                max_num_data_points: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_tampered_blocks() {
+        let mut rng = ark_std::test_rng();
+        let encoder = HybridEncoder::<Bn254Parameters>::new();
+        let block_len = encoder.get_block_len();
+
+        let mut test_bytes = vec![0u8; encoder.get_capacity()];
+        rng.fill_bytes(&mut test_bytes[..]);
+        let points = encoder.encode(&test_bytes, &mut rng);
+
+        assert_eq!(
+            encoder.decode(&points[..block_len - 1]),
+            Err(HybridDecodeError::WrongLength {
+                expected: block_len,
+                found: block_len - 1
+            })
+        );
+
+        let mut tampered = points.clone();
+        tampered[3].y += &Fq::one();
+        assert_eq!(
+            encoder.decode(&tampered),
+            Err(HybridDecodeError::PointNotOnCurve(3))
+        );
+
+        // a valid point in place of the hint point
+        for _ in 0..REPETITIONS {
+            let mut tampered = points.clone();
+            tampered[block_len - 1] =
+                G1Affine::<Bn254Parameters>::from(G1Projective::rand(&mut rng));
+            assert_eq!(
+                encoder.decode(&tampered),
+                Err(HybridDecodeError::HintTagMismatch)
+            );
+        }
+
+        // a valid point in place of a data point
+        for _ in 0..REPETITIONS {
+            let mut tampered = points.clone();
+            tampered[5] = G1Affine::<Bn254Parameters>::from(G1Projective::rand(&mut rng));
+            assert!(matches!(
+                encoder.decode(&tampered),
+                Err(HybridDecodeError::InvalidDataPoint(5, _))
+                    | Err(HybridDecodeError::DataOutOfRange(5))
+            ));
+        }
+    }
+
+    fn test_compact_rand_for<P: ark_ec::bn::BnParameters + Sync>(num_data_points: usize) {
+        let mut rng = ark_std::test_rng();
+        let encoder = HybridEncoder::<P>::new();
+
+        let mut test_bytes = vec![0u8; num_data_points * encoder.num_bytes_per_point];
+        rng.fill_bytes(&mut test_bytes[..]);
+
+        let points = encoder.encode_compact(&test_bytes, &mut rng).unwrap();
+        assert_eq!(encoder.decode_compact(&points).unwrap(), test_bytes);
+
+        let report = encoder.measure_density(&test_bytes, &mut rng).unwrap();
+        assert_eq!(report.num_points, points.len());
+        assert!(report.compact_bytes_per_point() > report.fixed_bytes_per_point());
+
+        // the empty input is a single hint point
+        let points = encoder.encode_compact(&[], &mut rng).unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(encoder.decode_compact(&points).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_compact_rand() {
+        test_compact_rand_for::<Bn254Parameters>(1000);
+        test_compact_rand_for::<Bn446Parameters>(500);
+    }
+
+    #[test]
+    fn test_compact_tampered() {
+        let mut rng = ark_std::test_rng();
+        let encoder = HybridEncoder::<Bn254Parameters>::new();
+
+        let mut test_bytes = vec![0u8; 200 * encoder.num_bytes_per_point];
+        rng.fill_bytes(&mut test_bytes[..]);
+        let points = encoder.encode_compact(&test_bytes, &mut rng).unwrap();
+
+        assert_eq!(
+            encoder.decode_compact(&points[..points.len() - 1]),
+            Err(HybridDecodeError::WrongLength {
+                expected: points.len(),
+                found: points.len() - 1
+            })
+        );
+        assert_eq!(
+            encoder.decode_compact(&[]),
+            Err(HybridDecodeError::WrongLength {
+                expected: 1,
+                found: 0
+            })
+        );
+
+        let mut tampered = points.clone();
+        tampered[3].y += &Fq::one();
+        assert_eq!(
+            encoder.decode_compact(&tampered),
+            Err(HybridDecodeError::PointNotOnCurve(3))
+        );
+
+        for _ in 0..REPETITIONS {
+            let mut tampered = points.clone();
+            tampered[0] = G1Affine::<Bn254Parameters>::from(G1Projective::rand(&mut rng));
+            assert_eq!(
+                encoder.decode_compact(&tampered),
+                Err(HybridDecodeError::HintTagMismatch)
+            );
+        }
+
+        // the data points are not tagged, so a random point in their place is only caught
+        // when it has no candidate for its hint bits
+        for _ in 0..REPETITIONS {
+            let mut tampered = points.clone();
+            tampered[5] = G1Affine::<Bn254Parameters>::from(G1Projective::rand(&mut rng));
+            assert_ne!(encoder.decode_compact(&tampered), Ok(test_bytes.clone()));
+        }
+
+        // and an unambiguous data point replaced by the point of another unambiguous value decodes
+        // to other bytes without an error, since the compact encoding is unauthenticated
+        let index = (1..points.len())
+            .find(|i| encoder.compact_candidates(points[*i]).len() == 1)
+            .unwrap();
+        let replacement = (0u64..)
+            .map(|v| encoder.encoder.encode(Fq::from(v), &mut rng).0)
+            .find(|p| encoder.compact_candidates(*p).len() == 1 && *p != points[index])
+            .unwrap();
+        let mut tampered = points.clone();
+        tampered[index] = replacement;
+        let decoded = encoder.decode_compact(&tampered).unwrap();
+        assert_eq!(decoded.len(), test_bytes.len());
+        assert_ne!(decoded, test_bytes);
+    }
+
+    #[test]
+    fn test_compact_errors() {
+        let mut rng = ark_std::test_rng();
+        let encoder = HybridEncoder::<Bn254Parameters>::new();
+
+        let test_bytes = vec![0u8; encoder.num_bytes_per_point + 1];
+        assert_eq!(
+            encoder.encode_compact(&test_bytes, &mut rng),
+            Err(CompactEncodeError::WrongLength {
+                num_bytes: encoder.num_bytes_per_point + 1,
+                num_bits_per_point: encoder.num_bits_per_point
+            })
+        );
+
+        // a tag that fills the hint point up to the count leaves no bits for the hints
+        let encoder = HybridEncoder::<Bn254Parameters>::builder()
+            .tag_len(encoder.num_bytes_per_point - 2)
+            .num_data_points(1)
+            .build()
+            .unwrap();
+        let test_bytes = vec![0u8; encoder.num_bytes_per_point];
+        assert_eq!(
+            encoder.encode_compact(&test_bytes, &mut rng),
+            Err(CompactEncodeError::NoRoomForHints { num_hint_bits: 0 })
+        );
+        assert_eq!(
+            encoder.measure_density(&test_bytes, &mut rng),
+            Err(CompactEncodeError::NoRoomForHints { num_hint_bits: 0 })
+        );
+    }
+
+    fn test_bit_packing_for<P: ark_ec::bn::BnParameters + Sync>(
+        num_bits_per_point: usize,
+        num_data_points: usize,
+    ) {
+        let mut rng = ark_std::test_rng();
+        let encoder = HybridEncoder::<P>::builder()
+            .bit_packing(true)
+            .build()
+            .unwrap();
+        assert_eq!(encoder.num_bits_per_point, num_bits_per_point);
+        assert_eq!(encoder.num_data_points, num_data_points);
+        assert_eq!(
+            encoder.get_capacity(),
+            num_bits_per_point * num_data_points / 8
+        );
+
+        // denser than the whole bytes
+        let byte_encoder = HybridEncoder::<P>::new();
+        assert!(
+            encoder.get_capacity() * byte_encoder.get_block_len()
+                > byte_encoder.get_capacity() * encoder.get_block_len()
+        );
+
+        assert_eq!(
+            HybridEncoder::<P>::builder()
+                .bit_packing(true)
+                .num_data_points(num_data_points + 1)
+                .build()
+                .err(),
+            Some(HybridEncoderError::HintsDoNotFit {
+                num_data_points: num_data_points + 1,
+                max_num_data_points: num_data_points,
+            })
+        );
+
+        // the largest values of every point round trip too
+        let mut test_bytes = vec![0xffu8; encoder.get_capacity()];
+        for i in 0..REPETITIONS {
+            let points = encoder.encode(&test_bytes, &mut rng);
+            assert_eq!(encoder.decode(&points).unwrap(), test_bytes);
+
+            if i == 0 {
+                rng.fill_bytes(&mut test_bytes[..]);
+            }
+        }
+
+        let mut message = vec![0u8; encoder.get_capacity() + 17];
+        rng.fill_bytes(&mut message[..]);
+        let points = encoder.encode_message(&message, &mut rng);
+        assert_eq!(encoder.decode_message(&points).unwrap(), message);
+
+        // the compact encoding takes a whole number of points
+        let mut test_bytes = vec![0u8; num_bits_per_point * 4];
+        rng.fill_bytes(&mut test_bytes[..]);
+        let points = encoder.encode_compact(&test_bytes, &mut rng).unwrap();
+        assert_eq!(encoder.decode_compact(&points).unwrap(), test_bytes);
+    }
+
+    #[test]
+    fn test_bit_packing() {
+        test_bit_packing_for::<Bn254Parameters>(253, 86);
+        test_bit_packing_for::<Bn446Parameters>(445, 182);
+    }
 }
@@ -0,0 +1,280 @@
+//! The Jacobi symbol over the limbs of [`BigInteger`], in pure Rust.
+//!
+//! This is the variable-time "posdivsteps" variant of the Bernstein-Yang safegcd algorithm,
+//! following `secp256k1_jacobi64_maybe_var` in libsecp256k1. Each batch of 62 steps only
+//! looks at the lowest 64 bits of the two numbers, and the resulting transition matrix is then
+//! applied to the full numbers represented in signed 62-bit limbs. All the values stay
+//! non-negative, so the Jacobi symbol can be tracked with the usual rules for division by two
+//! and quadratic reciprocity.
+
+use ark_ff::BigInteger;
+
+/// The maximal number of 62-bit limbs, enough for 960-bit integers.
+const MAX_LIMBS: usize = 16;
+
+const M62: u64 = u64::MAX >> 2;
+
+struct Signed62 {
+    v: [i64; MAX_LIMBS],
+}
+
+impl Signed62 {
+    fn from_bigint<B: BigInteger>(a: &B) -> Self {
+        let mut v = [0i64; MAX_LIMBS];
+
+        for (i, limb) in a.as_ref().iter().enumerate() {
+            let pos = i * 64;
+            let idx = pos / 62;
+            let shift = pos % 62;
+
+            v[idx] |= ((limb << shift) & M62) as i64;
+            // since 64 + shift <= 124, the limb spans at most two 62-bit limbs
+            v[idx + 1] |= ((limb >> (62 - shift)) & M62) as i64;
+        }
+
+        Self { v }
+    }
+}
+
+/// The transition matrix of 62 posdivsteps, scaled by 2^62.
+struct Transition {
+    u: u64,
+    v: u64,
+    q: u64,
+    r: u64,
+}
+
+/// Perform 62 posdivsteps on the lowest 64 bits of `f` and `g`.
+/// The lowest bit of `jac` is flipped every time the Jacobi symbol `(g | f)` changes sign.
+fn posdivsteps_62(mut eta: i64, f0: u64, g0: u64, jac: &mut u64) -> (i64, Transition) {
+    let (mut u, mut v, mut q, mut r) = (1u64, 0u64, 0u64, 1u64);
+    let (mut f, mut g) = (f0, g0);
+    let mut i = 62u32;
+
+    loop {
+        // use a sentinel bit to count zeros only up to i, and divide g by two that many times
+        let zeros = (g | (u64::MAX << i)).trailing_zeros();
+        g >>= zeros;
+        u <<= zeros;
+        v <<= zeros;
+        eta -= zeros as i64;
+        i -= zeros;
+
+        // dividing g by an odd power of two flips the sign if f = 3 or 5 (mod 8)
+        *jac ^= (zeros as u64) & ((f >> 1) ^ (f >> 2));
+
+        if i == 0 {
+            break;
+        }
+
+        let swapped = eta < 0;
+        if swapped {
+            eta = -eta;
+            core::mem::swap(&mut f, &mut g);
+            core::mem::swap(&mut u, &mut q);
+            core::mem::swap(&mut v, &mut r);
+
+            // quadratic reciprocity flips the sign if f = g = 3 (mod 4)
+            *jac ^= (f & g) >> 1;
+        }
+
+        // no more than i bits can be cancelled, and no more than eta + 1 before eta flips again
+        let limit = if eta + 1 > i as i64 {
+            i
+        } else {
+            (eta + 1) as u32
+        };
+        let (m, w) = if swapped {
+            // cancel up to 6 bits of g with a multiple of f
+            let m = (u64::MAX >> (64 - limit)) & 63;
+            let w = f
+                .wrapping_mul(g)
+                .wrapping_mul(f.wrapping_mul(f).wrapping_sub(2))
+                & m;
+            (m, w)
+        } else {
+            // cancel up to 4 bits of g with a multiple of f
+            let m = (u64::MAX >> (64 - limit)) & 15;
+            let w = f.wrapping_add((f.wrapping_add(1) & 4) << 1);
+            (m, w.wrapping_neg().wrapping_mul(g) & m)
+        };
+
+        g = g.wrapping_add(f.wrapping_mul(w));
+        q = q.wrapping_add(u.wrapping_mul(w));
+        r = r.wrapping_add(v.wrapping_mul(w));
+        debug_assert_eq!(g & m, 0);
+    }
+
+    (eta, Transition { u, v, q, r })
+}
+
+/// Replace `(f, g)` by `(u * f + v * g, q * f + r * g) / 2^62`.
+fn update_fg(len: usize, f: &mut Signed62, g: &mut Signed62, t: &Transition) {
+    let (u, v, q, r) = (t.u as i128, t.v as i128, t.q as i128, t.r as i128);
+
+    let mut cf = u * f.v[0] as i128 + v * g.v[0] as i128;
+    let mut cg = q * f.v[0] as i128 + r * g.v[0] as i128;
+    debug_assert_eq!(cf as u64 & M62, 0);
+    debug_assert_eq!(cg as u64 & M62, 0);
+    cf >>= 62;
+    cg >>= 62;
+
+    for i in 1..len {
+        cf += u * f.v[i] as i128 + v * g.v[i] as i128;
+        cg += q * f.v[i] as i128 + r * g.v[i] as i128;
+        f.v[i - 1] = (cf as u64 & M62) as i64;
+        g.v[i - 1] = (cg as u64 & M62) as i64;
+        cf >>= 62;
+        cg >>= 62;
+    }
+
+    f.v[len - 1] = cf as i64;
+    g.v[len - 1] = cg as i64;
+}
+
+/// Compute the Jacobi symbol `(a | n)` for an odd `n > 1` and `0 <= a < n` with `gcd(a, n) = 1`.
+///
+/// Returns `None` in the rare case that the algorithm does not converge within its iteration
+/// budget, in which case the caller should fall back to another method.
+pub fn jacobi<B: BigInteger>(a: &B, n: &B) -> Option<i32> {
+    debug_assert!(n.is_odd());
+
+    if a.is_zero() {
+        return Some(0);
+    }
+
+    let mut f = Signed62::from_bigint(n);
+    let mut g = Signed62::from_bigint(a);
+
+    let mut len = (n.num_bits() as usize).div_ceil(62) + 1;
+    assert!(len <= MAX_LIMBS);
+
+    let mut eta = -1i64;
+    let mut jac = 0u64;
+
+    // A budget of about 3 posdivsteps per bit, which the algorithm almost always meets.
+    let max_batches = (n.num_bits() as usize * 3) / 62 + 4;
+
+    for _ in 0..max_batches {
+        let f0 = (f.v[0] as u64) | ((f.v[1] as u64) << 62);
+        let g0 = (g.v[0] as u64) | ((g.v[1] as u64) << 62);
+
+        let (eta_new, t) = posdivsteps_62(eta, f0, g0, &mut jac);
+        eta = eta_new;
+        update_fg(len, &mut f, &mut g, &t);
+
+        // if f = 1, then the Jacobi symbol is determined
+        if f.v[0] == 1 && f.v[1..len].iter().all(|limb| *limb == 0) {
+            return Some(1 - 2 * (jac & 1) as i32);
+        }
+
+        // reduce the length if the top limbs of both f and g are zero
+        if len > 1 && f.v[len - 1] == 0 && g.v[len - 1] == 0 {
+            len -= 1;
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use crate::curve_bn254::Fq as Bn254Fq;
+    use crate::curve_bn446::Fq as Bn446Fq;
+    use crate::message_encoding::jacobi::jacobi;
+    use ark_ff::{
+        biginteger::BigInteger256, FpParameters, LegendreSymbol, PrimeField, SquareRootField,
+    };
+    use ark_std::UniformRand;
+
+    const REPETITIONS: u64 = 1000;
+
+    fn legendre<F: SquareRootField>(a: F) -> i32 {
+        match a.legendre() {
+            LegendreSymbol::Zero => 0,
+            LegendreSymbol::QuadraticResidue => 1,
+            LegendreSymbol::QuadraticNonResidue => -1,
+        }
+    }
+
+    // The textbook algorithm, for small integers.
+    fn jacobi_u64(mut a: u64, mut n: u64) -> i32 {
+        let mut t = 1;
+        a %= n;
+        while a != 0 {
+            while a.is_multiple_of(2) {
+                a /= 2;
+                if n % 8 == 3 || n % 8 == 5 {
+                    t = -t;
+                }
+            }
+            core::mem::swap(&mut a, &mut n);
+            if a % 4 == 3 && n % 4 == 3 {
+                t = -t;
+            }
+            a %= n;
+        }
+        if n == 1 {
+            t
+        } else {
+            0
+        }
+    }
+
+    fn gcd(a: u64, b: u64) -> u64 {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+
+    #[test]
+    fn test_jacobi_prime_modulus() {
+        let mut rng = ark_std::test_rng();
+
+        for _ in 0..REPETITIONS {
+            let a = Bn254Fq::rand(&mut rng);
+            assert_eq!(
+                jacobi(&a.into_repr(), &<Bn254Fq as PrimeField>::Params::MODULUS),
+                Some(legendre(a))
+            );
+
+            let a = Bn446Fq::rand(&mut rng);
+            assert_eq!(
+                jacobi(&a.into_repr(), &<Bn446Fq as PrimeField>::Params::MODULUS),
+                Some(legendre(a))
+            );
+        }
+
+        let minus_one = -Bn446Fq::from(1u64);
+        assert_eq!(
+            jacobi(
+                &minus_one.into_repr(),
+                &<Bn446Fq as PrimeField>::Params::MODULUS
+            ),
+            Some(legendre(minus_one))
+        );
+    }
+
+    #[test]
+    fn test_jacobi_composite_modulus() {
+        for n in (3u64..300).step_by(2) {
+            for a in 0..n {
+                if gcd(a, n) == 1 {
+                    assert_eq!(
+                        jacobi(&BigInteger256::from(a), &BigInteger256::from(n)),
+                        Some(jacobi_u64(a, n))
+                    );
+                }
+            }
+        }
+
+        let n = 0xffff_ffff_ffff_ffc5u64; // the largest 64-bit prime
+        let a = 0x1234_5678_9abc_def0u64;
+        assert_eq!(
+            jacobi(&BigInteger256::from(a), &BigInteger256::from(n)),
+            Some(jacobi_u64(a, n))
+        );
+    }
+}
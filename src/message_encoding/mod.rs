@@ -1,76 +1,202 @@
-use ark_ec::bn::{BnParameters, G1Affine};
+use ark_ec::short_weierstrass_jacobian::GroupAffine;
 use ark_ec::SWModelParameters;
-use ark_ff::{Field, FpParameters, LegendreSymbol, One, PrimeField, SquareRootField, Zero};
+use ark_ff::{
+    batch_inversion, FftParameters, Field, FpParameters, LegendreSymbol, One, PrimeField,
+    SquareRootField, Zero,
+};
 use ark_std::cmp::Ordering;
-use ark_std::mem::MaybeUninit;
+use ark_std::fmt;
 use ark_std::ops::{Add, BitAnd, Div};
 use ark_std::rand::RngCore;
 use ark_std::{marker::PhantomData, ops::Neg, vec::Vec, UniformRand};
-use gmp_mpfr_sys::gmp;
 use num_bigint::BigUint;
-use subtle::{ConditionallySelectable, ConstantTimeEq};
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+#[cfg(feature = "gmp")]
+use ark_std::mem::MaybeUninit;
+#[cfg(feature = "gmp")]
+use gmp_mpfr_sys::gmp;
 
+pub mod constant_time;
+pub mod elligator_squared;
+pub mod g2;
 pub mod hybrid;
+pub mod jacobi;
+#[cfg(feature = "std")]
+pub mod stream;
 
 pub type DecodeHint = u8;
 
-pub struct Encoder<P: BnParameters> {
-    pub q: gmp::mpz_t,
+/// The errors of decoding a point with a hint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The hint is not in 1..=4.
+    InvalidHint(DecodeHint),
+    /// The point is not on the curve.
+    PointNotOnCurve,
+    /// The point has no preimage for the hint.
+    NoPreimage,
+    /// The decoded value is not encoded back to the same point and hint.
+    ReencodingMismatch,
+}
 
-    pub b: P::Fp,
-    pub b_plus_one: P::Fp,
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidHint(hint) => write!(f, "invalid decode hint {}", hint),
+            DecodeError::PointNotOnCurve => write!(f, "the point is not on the curve"),
+            DecodeError::NoPreimage => write!(f, "the point has no preimage for the hint"),
+            DecodeError::ReencodingMismatch => {
+                write!(
+                    f,
+                    "the decoded value does not encode to the point and the hint"
+                )
+            }
+        }
+    }
+}
 
-    pub minus_one: P::Fp,
-    pub minus_3: P::Fp,
-    pub minus_4_times_b_plus_1: P::Fp,
+impl ark_std::error::Error for DecodeError {}
+
+/// The errors of constructing an encoder for unsupported curve parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EncoderError {
+    /// The curve is not of the form y^2 = x^3 + b.
+    NonZeroCoefficientA,
+    /// The coefficient b is 0 or -1.
+    UnsupportedCoefficientB,
+    /// -3 is not a square, so the encoding is undefined.
+    MinusThreeNotSquare,
+}
+
+impl fmt::Display for EncoderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncoderError::NonZeroCoefficientA => write!(f, "the coefficient a is not zero"),
+            EncoderError::UnsupportedCoefficientB => write!(f, "the coefficient b is 0 or -1"),
+            EncoderError::MinusThreeNotSquare => write!(f, "-3 is not a square"),
+        }
+    }
+}
 
-    pub inv_2: P::Fp,
+impl ark_std::error::Error for EncoderError {}
+
+pub struct Encoder<P: SWModelParameters>
+where
+    P::BaseField: PrimeField,
+{
+    pub b: P::BaseField,
+    pub b_plus_one: P::BaseField,
+
+    pub minus_one: P::BaseField,
+    pub minus_3: P::BaseField,
+    pub minus_4_times_b_plus_1: P::BaseField,
+
+    pub inv_2: P::BaseField,
     pub legendre_2: i32,
 
-    pub sqrt_minus_3: P::Fp,
-    pub minus_sqrt_minus_3: P::Fp,
-    pub sqrt_minus_3_minus_1_div_2: P::Fp,
-    pub minus_sqrt_minus_3_div_2: P::Fp,
+    pub sqrt_minus_3: P::BaseField,
+    pub minus_sqrt_minus_3: P::BaseField,
+    pub sqrt_minus_3_minus_1_div_2: P::BaseField,
+    pub minus_sqrt_minus_3_div_2: P::BaseField,
+
+    pub q_minus_1_div_2: P::BaseField,
 
-    pub q_minus_1_div_2: P::Fp,
+    /// The two-adicity s of q - 1 = 2^s t, which selects the square root method.
+    pub two_adicity: u32,
 
-    /// [u64] representation of (q + 1) / 4.
-    /// Powering a quadratic residue by this, we can obtain one of the square root, for q == 3 (mod 4).
+    /// [u64] representation of (q + 1) / 4 if s = 1, and of (t - 1) / 2 otherwise.
+    /// Powering a quadratic residue by (q + 1) / 4, we can obtain one of the square root, for q == 3 (mod 4).
+    /// Otherwise, this is the first exponentiation of Tonelli-Shanks.
     pub square_root_pow: Vec<u64>,
 
+    /// A primitive 2^s-th root of unity for Tonelli-Shanks, which is a non-residue to the power of t.
+    pub two_adic_root_of_unity: P::BaseField,
+
+    /// The exceptional inputs, where the formulas of the encoding are undefined, with their outputs.
+    /// Following [FT10], t = 0 is mapped to ((-1 + sqrt(-3)) / 2, sqrt(1 + b)) with hint 1 if 1 + b is a square.
+    /// Otherwise, t = 0 and the two roots of t^2 = -(1 + b) are mapped to the point at infinity,
+    /// with the hints 1, 2 (the positive root), and 3 (the negative root).
+    pub exceptional_inputs: Vec<(P::BaseField, GroupAffine<P>, DecodeHint)>,
+
     #[doc(hidden)]
     pub phantom: PhantomData<P>,
 }
 
-unsafe impl<P: BnParameters> Sync for Encoder<P> {}
+impl<P: SWModelParameters> Clone for Encoder<P>
+where
+    P::BaseField: PrimeField,
+{
+    fn clone(&self) -> Self {
+        Self {
+            b: self.b,
+            b_plus_one: self.b_plus_one,
 
-impl<P: BnParameters> Encoder<P> {
-    pub fn new() -> Self {
-        let q = unsafe {
-            let mut q = MaybeUninit::uninit();
-            gmp::mpz_init_set_ui(q.as_mut_ptr(), 0u64);
+            minus_one: self.minus_one,
+            minus_3: self.minus_3,
+            minus_4_times_b_plus_1: self.minus_4_times_b_plus_1,
 
-            let mut q = q.assume_init();
+            inv_2: self.inv_2,
+            legendre_2: self.legendre_2,
 
-            let repr = <<P as BnParameters>::Fp as PrimeField>::Params::MODULUS;
-            let limbs: &[u64] = AsRef::<[u64]>::as_ref(&repr);
-            for limb in limbs.iter().rev() {
-                gmp::mpz_mul_2exp(&mut q, &q, 64);
-                gmp::mpz_add_ui(&mut q, &q, *limb);
-            }
+            sqrt_minus_3: self.sqrt_minus_3,
+            minus_sqrt_minus_3: self.minus_sqrt_minus_3,
+            sqrt_minus_3_minus_1_div_2: self.sqrt_minus_3_minus_1_div_2,
+            minus_sqrt_minus_3_div_2: self.minus_sqrt_minus_3_div_2,
 
-            q
-        };
+            q_minus_1_div_2: self.q_minus_1_div_2,
+
+            two_adicity: self.two_adicity,
+            square_root_pow: self.square_root_pow.clone(),
+            two_adic_root_of_unity: self.two_adic_root_of_unity,
+            exceptional_inputs: self.exceptional_inputs.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// The encoder for G1 of BN254, computed once on first use.
+#[cfg(feature = "std")]
+pub fn bn254_encoder() -> &'static Encoder<crate::curve_bn254::g1::Parameters> {
+    static ENCODER: OnceLock<Encoder<crate::curve_bn254::g1::Parameters>> = OnceLock::new();
+    ENCODER.get_or_init(|| Encoder::new().unwrap())
+}
+
+/// The encoder for G1 of BN446, computed once on first use.
+#[cfg(feature = "std")]
+pub fn bn446_encoder() -> &'static Encoder<crate::curve_bn446::g1::Parameters> {
+    static ENCODER: OnceLock<Encoder<crate::curve_bn446::g1::Parameters>> = OnceLock::new();
+    ENCODER.get_or_init(|| Encoder::new().unwrap())
+}
+
+impl<P: SWModelParameters> Encoder<P>
+where
+    P::BaseField: PrimeField,
+{
+    /// Construct the encoder for the curve y^2 = x^3 + b, or return an error if the curve is not supported.
+    pub fn new() -> Result<Self, EncoderError> {
+        if !P::COEFF_A.is_zero() {
+            return Err(EncoderError::NonZeroCoefficientA);
+        }
+        // b = 0 gives a singular curve, and b = -1 makes t = 0 a root of 1 + b + t^2
+        if P::COEFF_B.is_zero() || (P::COEFF_B + &P::BaseField::one()).is_zero() {
+            return Err(EncoderError::UnsupportedCoefficientB);
+        }
+        if P::BaseField::from(3u64).neg().legendre() != LegendreSymbol::QuadraticResidue {
+            return Err(EncoderError::MinusThreeNotSquare);
+        }
 
-        let b = P::G1Parameters::COEFF_B;
-        let b_plus_one = b + &<P as BnParameters>::Fp::one();
+        let b = P::COEFF_B;
+        let b_plus_one = b + &P::BaseField::one();
 
-        let minus_one = <P as BnParameters>::Fp::one().neg();
-        let minus_3: P::Fp = <P as BnParameters>::Fp::from(3u64).neg();
-        let minus_4_times_b_plus_1 = <P as BnParameters>::Fp::from(4u64).neg() * &b_plus_one;
+        let minus_one = P::BaseField::one().neg();
+        let minus_3: P::BaseField = P::BaseField::from(3u64).neg();
+        let minus_4_times_b_plus_1 = P::BaseField::from(4u64).neg() * &b_plus_one;
 
-        let inv_2 = <P as BnParameters>::Fp::from(2u64).inverse().unwrap();
-        let legendre_2 = match <P as BnParameters>::Fp::from(2u64).legendre() {
+        let inv_2 = P::BaseField::from(2u64).inverse().unwrap();
+        let legendre_2 = match P::BaseField::from(2u64).legendre() {
             LegendreSymbol::Zero => 0,
             LegendreSymbol::QuadraticResidue => 1,
             LegendreSymbol::QuadraticNonResidue => -1,
@@ -81,21 +207,29 @@ impl<P: BnParameters> Encoder<P> {
         let sqrt_minus_3_minus_1_div_2 = (minus_one + &sqrt_minus_3) * &inv_2;
         let minus_sqrt_minus_3_div_2 = inv_2 * &minus_sqrt_minus_3;
 
-        let q_minus_1_div_2 = P::Fp::from_repr(
-            <<P as BnParameters>::Fp as PrimeField>::Params::MODULUS_MINUS_ONE_DIV_TWO,
+        let q_minus_1_div_2 = P::BaseField::from_repr(
+            <P::BaseField as PrimeField>::Params::MODULUS_MINUS_ONE_DIV_TWO,
         )
         .unwrap();
 
+        // the square roots are computed by powering to (q + 1) / 4 for q == 3 (mod 4),
+        // and by Tonelli-Shanks otherwise
+        let two_adicity = <<P::BaseField as PrimeField>::Params as FftParameters>::TWO_ADICITY;
+
         let square_root_pow = {
-            let tmp: BigUint = <<P as BnParameters>::Fp as PrimeField>::Params::MODULUS
-                .into()
-                .add(1u64)
-                .div(4u64);
+            let tmp: BigUint = if two_adicity == 1 {
+                <P::BaseField as PrimeField>::Params::MODULUS
+                    .into()
+                    .add(1u64)
+                    .div(4u64)
+            } else {
+                <P::BaseField as PrimeField>::Params::T_MINUS_ONE_DIV_TWO.into()
+            };
             let bytes = tmp.to_bytes_le();
 
             let mut limbs = Vec::new();
 
-            bytes.chunks(8).into_iter().for_each(|chunk| {
+            bytes.chunks(8).for_each(|chunk| {
                 let mut chunk_padded = [0u8; 8];
                 chunk_padded[..chunk.len()].copy_from_slice(chunk);
                 limbs.push(u64::from_le_bytes(chunk_padded))
@@ -104,9 +238,48 @@ impl<P: BnParameters> Encoder<P> {
             limbs
         };
 
-        Self {
-            q,
+        let two_adic_root_of_unity = {
+            let non_residue = (2u64..)
+                .map(P::BaseField::from)
+                .find(|v| v.legendre() == LegendreSymbol::QuadraticNonResidue)
+                .unwrap();
+            non_residue.pow(<P::BaseField as PrimeField>::Params::T)
+        };
 
+        let exceptional_inputs = {
+            let positive = |v: P::BaseField| {
+                if v.cmp(&q_minus_1_div_2) == Ordering::Less {
+                    v
+                } else {
+                    v.neg()
+                }
+            };
+
+            let mut exceptional_inputs = Vec::new();
+            match b_plus_one.sqrt() {
+                Some(sqrt_b_plus_one) => exceptional_inputs.push((
+                    P::BaseField::zero(),
+                    GroupAffine::<P>::new(
+                        sqrt_minus_3_minus_1_div_2,
+                        positive(sqrt_b_plus_one),
+                        false,
+                    ),
+                    1,
+                )),
+                None => {
+                    exceptional_inputs.push((P::BaseField::zero(), GroupAffine::<P>::zero(), 1))
+                }
+            }
+            if let Some(root) = b_plus_one.neg().sqrt() {
+                let root = positive(root);
+                exceptional_inputs.push((root, GroupAffine::<P>::zero(), 2));
+                exceptional_inputs.push((root.neg(), GroupAffine::<P>::zero(), 3));
+            }
+
+            exceptional_inputs
+        };
+
+        Ok(Self {
             b,
             b_plus_one,
 
@@ -124,174 +297,255 @@ impl<P: BnParameters> Encoder<P> {
 
             q_minus_1_div_2,
 
+            two_adicity,
             square_root_pow,
+            two_adic_root_of_unity,
+            exceptional_inputs,
             phantom: PhantomData,
-        }
+        })
     }
 
-    pub fn encode<R: RngCore>(&self, val: P::Fp, rng: &mut R) -> (G1Affine<P>, DecodeHint) {
+    /// Encode a field element into a point, with branch-free selection between the cases.
+    pub fn encode<R: RngCore>(
+        &self,
+        val: P::BaseField,
+        rng: &mut R,
+    ) -> (GroupAffine<P>, DecodeHint) {
         // The following algorithm from [FT10] is equivalent to the original formulas for x_1, x_2, x_3
         // [FT10]: Pierre-Alain Fouque and Mehdi Tibouchi, "Indifferentiable Hashing to Barreto–Naehrig Curves", LATINCRYPT 2012.
 
         // val is v on the paper
         // w is 1/u; w.inverse() = u
-        let w =
-            self.minus_sqrt_minus_3 * &val * &(self.b_plus_one + &val.square()).inverse().unwrap();
+        // the exceptional inputs make the inversions fail, which are replaced by zero for now
+        let w = self.minus_sqrt_minus_3
+            * &val
+            * &constant_time::blinded_inverse_or_zero(self.b_plus_one + &val.square(), rng);
+        let u = constant_time::blinded_inverse_or_zero(w, rng);
+
+        self.encode_with_inverses(val, w, u, rng)
+    }
 
+    /// Encode the field elements as [`Self::encode`] does, with the inversions shared across the batch.
+    pub fn encode_batch<R: RngCore>(
+        &self,
+        vals: &[P::BaseField],
+        rng: &mut R,
+    ) -> Vec<(GroupAffine<P>, DecodeHint)> {
+        let mut w: Vec<P::BaseField> = vals
+            .iter()
+            .map(|val| self.b_plus_one + &val.square())
+            .collect();
+        constant_time::blinded_batch_inverse_or_zero(&mut w, rng);
+        for (w, val) in w.iter_mut().zip(vals.iter()) {
+            *w *= &(self.minus_sqrt_minus_3 * val);
+        }
+
+        let mut u = w.clone();
+        constant_time::blinded_batch_inverse_or_zero(&mut u, rng);
+
+        vals.iter()
+            .zip(w)
+            .zip(u)
+            .map(|((val, w), u)| self.encode_with_inverses(*val, w, u, rng))
+            .collect()
+    }
+
+    /// The rest of the encoding after the inversions, with w = -sqrt(-3) val / (1 + b + val^2) and u = 1/w.
+    fn encode_with_inverses<R: RngCore>(
+        &self,
+        val: P::BaseField,
+        w: P::BaseField,
+        u: P::BaseField,
+        rng: &mut R,
+    ) -> (GroupAffine<P>, DecodeHint) {
         let x1 = self.sqrt_minus_3_minus_1_div_2 + val * &w;
         let x2 = self.minus_one - &x1;
 
-        let u = w.inverse().unwrap_or_default();
-        let x3 = <P as BnParameters>::Fp::one() + &u.square();
+        let x3 = P::BaseField::one() + &u.square();
 
         // Sample r1 and r2 for data-independent-time masking
-        let r1 = P::Fp::rand(rng);
-        let r2 = P::Fp::rand(rng);
+        let r1 = P::BaseField::rand(rng);
+        let r2 = P::BaseField::rand(rng);
 
         // Compute the Legendre symbols of (r1 ^ 2 * (x1 ^ 3 + b)) and (r2 ^ 2 * (x2 ^ 3 + b))
         let alpha = self.compute_legendre_symbol(r1.square() * &(x1.square() * &x1 + &self.b));
         let beta = self.compute_legendre_symbol(r2.square() * &(x2.square() * &x2 + &self.b));
 
-        let idx = (((alpha - 1) * beta + 3) % 3 + 1) as u8;
+        // idx = 1 if alpha = 1, idx = 2 if alpha = -1 and beta = 1, and idx = 3 otherwise
+        let idx = {
+            let mut idx = 3u8;
+            idx.conditional_assign(&2u8, beta.ct_eq(&1i32));
+            idx.conditional_assign(&1u8, alpha.ct_eq(&1i32));
+            idx
+        };
 
         let x = {
-            let mut x = x1;
-
-            let mut sel = idx.eq(&2u8) as u8;
-            x = x * &P::Fp::from((1 - sel) as u128) + x2 * &P::Fp::from(sel as u128);
-
-            sel = idx.eq(&3u8) as u8;
-            x = x * &P::Fp::from((1 - sel) as u128) + x3 * &P::Fp::from(sel as u128);
-
-            x
+            let x = constant_time::conditional_select(&x1, &x2, idx.ct_eq(&2u8));
+            constant_time::conditional_select(&x, &x3, idx.ct_eq(&3u8))
         };
 
-        let mut y = self.compute_square_root(x * &x.square() + &self.b);
+        let y = self.compute_square_root(x * &x.square() + &self.b);
 
         // Compute the special character
-        let sgn_cur = if y.cmp(&self.q_minus_1_div_2) == Ordering::Less {
-            1
-        } else {
-            -1
-        };
+        let sgn_cur = constant_time::is_positive(&y);
+        let sgn_expected = self.compute_character_choice(idx, val, u);
 
-        let sgn_expected = self.compute_character(idx, val, u);
+        let y = constant_time::conditional_select(&y, &y.neg(), sgn_cur ^ sgn_expected);
 
-        if sgn_cur != sgn_expected {
-            y = -y;
-        }
-
-        // Output the point * character;
-        let point = G1Affine::<P>::new(x.clone(), y, false);
-        assert!(point.is_on_curve());
-
-        let decode_hint = {
+        let mut decode_hint = {
             let mut x = 1;
             x.conditional_assign(&2u8, idx.ct_eq(&2u8));
 
-            let sgn_u_cur = if u.cmp(&self.q_minus_1_div_2) == Ordering::Less {
-                1i8
-            } else {
-                -1i8
-            };
+            let sgn_u_cur = constant_time::is_positive(&u);
 
-            x.conditional_assign(&3u8, idx.ct_eq(&3u8).bitand(sgn_u_cur.ct_eq(&1i8)));
-            x.conditional_assign(&4u8, idx.ct_eq(&3u8).bitand(sgn_u_cur.ct_eq(&-1i8)));
+            x.conditional_assign(&3u8, idx.ct_eq(&3u8).bitand(sgn_u_cur));
+            x.conditional_assign(&4u8, idx.ct_eq(&3u8).bitand(!sgn_u_cur));
 
             x
         };
 
+        // Replace the outputs of the exceptional inputs
+        let (mut x, mut y, mut infinity) = (x, y, Choice::from(0u8));
+        for (exceptional_val, exceptional_point, exceptional_hint) in self.exceptional_inputs.iter()
+        {
+            let is_exceptional = constant_time::ct_eq(&val, exceptional_val);
+            x = constant_time::conditional_select(&x, &exceptional_point.x, is_exceptional);
+            y = constant_time::conditional_select(&y, &exceptional_point.y, is_exceptional);
+            infinity.conditional_assign(
+                &Choice::from(exceptional_point.infinity as u8),
+                is_exceptional,
+            );
+            decode_hint.conditional_assign(exceptional_hint, is_exceptional);
+        }
+
+        // Output the point * character;
+        let point = GroupAffine::<P>::new(x, y, bool::from(infinity));
+        debug_assert!(point.is_on_curve());
+
         (point, decode_hint)
     }
 
-    pub fn compute_character(&self, idx: u8, val: P::Fp, u: P::Fp) -> i32 {
-        if idx == 1 || idx == 2 {
-            // CASE 1
-            if val.cmp(&self.q_minus_1_div_2) == Ordering::Less {
-                1
-            } else {
-                -1
-            }
-        } else {
-            // CASE 2
-            let delta_sqrt =
-                self.compute_square_root(self.minus_3 * &u.square() + &self.minus_4_times_b_plus_1);
-            let comp_number = u * self.minus_sqrt_minus_3_div_2 - &(delta_sqrt * &self.inv_2);
-            if val == comp_number {
-                1
-            } else {
-                -1
-            }
-        }
+    pub fn compute_character(&self, idx: u8, val: P::BaseField, u: P::BaseField) -> i32 {
+        i32::conditional_select(&-1, &1, self.compute_character_choice(idx, val, u))
+    }
+
+    /// The character as a [`Choice`], which is true for +1.
+    /// Both cases are always computed, and the result is selected without branching on them.
+    fn compute_character_choice(&self, idx: u8, val: P::BaseField, u: P::BaseField) -> Choice {
+        // CASE 1
+        let case_1 = constant_time::is_positive(&val);
+
+        // CASE 2
+        let delta_sqrt =
+            self.compute_square_root(self.minus_3 * &u.square() + &self.minus_4_times_b_plus_1);
+        let comp_number = u * self.minus_sqrt_minus_3_div_2 - &(delta_sqrt * &self.inv_2);
+        let case_2 = constant_time::ct_eq(&val, &comp_number);
+
+        let is_case_1 = idx.ct_eq(&1u8) | idx.ct_eq(&2u8);
+        (is_case_1 & case_1) | (!is_case_1 & case_2)
+    }
+
+    #[cfg(not(feature = "gmp"))]
+    #[inline]
+    pub fn compute_legendre_symbol(&self, val: P::BaseField) -> i32 {
+        // Compute the Legendre symbol via the binary Jacobi symbol algorithm over the limbs.
+        // Zero gives zero.
+
+        jacobi::jacobi(
+            &val.into_repr(),
+            &<P::BaseField as PrimeField>::Params::MODULUS,
+        )
+        .unwrap_or_else(|| match val.legendre() {
+            LegendreSymbol::Zero => 0,
+            LegendreSymbol::QuadraticResidue => 1,
+            LegendreSymbol::QuadraticNonResidue => -1,
+        })
     }
 
+    #[cfg(feature = "gmp")]
     #[inline]
-    pub fn compute_legendre_symbol(&self, val: P::Fp) -> i32 {
+    pub fn compute_legendre_symbol(&self, val: P::BaseField) -> i32 {
         // Compute the Legendre symbol via the law of quadratic reciprocity (in the Jacobi case).
-        assert!(!val.is_zero());
+        // Zero gives zero.
+
+        // the input and the modulus are read-only views of their limbs, so the call does not allocate
+        const _: () = assert!(gmp::LIMB_BITS == 64);
+        let repr = val.into_repr();
+        let limbs = repr.as_ref();
+        let modulus = <P::BaseField as PrimeField>::Params::MODULUS;
+        let modulus_limbs = modulus.as_ref();
 
-        let p = unsafe {
+        unsafe {
             let mut p = MaybeUninit::uninit();
-            gmp::mpz_init_set_ui(p.as_mut_ptr(), 0u64);
+            let p = gmp::mpz_roinit_n(
+                p.as_mut_ptr(),
+                limbs.as_ptr() as *const gmp::limb_t,
+                limbs.len() as gmp::size_t,
+            );
+            let mut q = MaybeUninit::uninit();
+            let q = gmp::mpz_roinit_n(
+                q.as_mut_ptr(),
+                modulus_limbs.as_ptr() as *const gmp::limb_t,
+                modulus_limbs.len() as gmp::size_t,
+            );
+            gmp::mpz_jacobi(p, q)
+        }
+    }
 
-            let mut p = p.assume_init();
+    /// Compute a square root of a square, with a fixed sequence of field operations.
+    #[inline]
+    pub fn compute_square_root(&self, val: P::BaseField) -> P::BaseField {
+        if self.two_adicity == 1 {
+            constant_time::pow_fixed_window(val, &self.square_root_pow)
+        } else {
+            constant_time::tonelli_shanks(
+                val,
+                self.two_adicity,
+                &self.square_root_pow,
+                self.two_adic_root_of_unity,
+            )
+        }
+    }
 
-            let repr = val.into_repr();
-            let limbs: &[u64] = AsRef::<[u64]>::as_ref(&repr);
-            for limb in limbs.iter().rev() {
-                gmp::mpz_mul_2exp(&mut p, &p, 64);
-                gmp::mpz_add_ui(&mut p, &p, *limb);
-            }
+    fn helper_decode_attempt_1_2(&self, x: P::BaseField, y: P::BaseField) -> Option<P::BaseField> {
+        let step_1 = (x - &self.sqrt_minus_3_minus_1_div_2).neg().inverse()?;
+        let step_2 = (step_1 * &self.sqrt_minus_3 + &self.minus_one).inverse()?;
+        self.helper_decode_attempt_1_2_with_inverses(step_2, y)
+    }
 
-            p
-        };
+    /// The rest of [`Self::helper_decode_attempt_1_2`] after the inversions.
+    fn helper_decode_attempt_1_2_with_inverses(
+        &self,
+        step_2: P::BaseField,
+        y: P::BaseField,
+    ) -> Option<P::BaseField> {
+        let step_3 = step_2 * &self.b_plus_one;
+        if self.compute_legendre_symbol(step_3) == -1 {
+            None
+        } else {
+            let step_4 = self.compute_square_root(step_3);
 
-        unsafe { gmp::mpz_jacobi(&p, &self.q) }
-    }
+            let sgn_cur = if step_4.cmp(&self.q_minus_1_div_2) == Ordering::Less {
+                1
+            } else {
+                -1
+            };
 
-    #[inline]
-    pub fn compute_square_root(&self, val: P::Fp) -> P::Fp {
-        val.pow(&self.square_root_pow)
-    }
-
-    fn helper_decode_attempt_1_2(&self, x: P::Fp, y: P::Fp) -> Option<P::Fp> {
-        let step_1 = (x - &self.sqrt_minus_3_minus_1_div_2).neg().inverse();
-        if step_1.is_some() {
-            let step_2 = (step_1.unwrap() * &self.sqrt_minus_3 + &self.minus_one).inverse();
-            if step_2.is_some() {
-                let step_3 = step_2.unwrap() * &self.b_plus_one;
-                if self.compute_legendre_symbol(step_3) == -1 {
-                    None
-                } else {
-                    let step_4 = self.compute_square_root(step_3);
-
-                    let sgn_cur = if step_4.cmp(&self.q_minus_1_div_2) == Ordering::Less {
-                        1
-                    } else {
-                        -1
-                    };
-
-                    let sgn_expected = if y.cmp(&self.q_minus_1_div_2) == Ordering::Less {
-                        1
-                    } else {
-                        -1
-                    };
-
-                    if sgn_cur != sgn_expected {
-                        Some(step_4.neg())
-                    } else {
-                        Some(step_4)
-                    }
-                }
+            let sgn_expected = if y.cmp(&self.q_minus_1_div_2) == Ordering::Less {
+                1
             } else {
-                None
+                -1
+            };
+
+            if sgn_cur != sgn_expected {
+                Some(step_4.neg())
+            } else {
+                Some(step_4)
             }
-        } else {
-            None
         }
     }
 
-    fn helper_decode_attempt_3_4(&self, u: P::Fp, y: P::Fp) -> Option<P::Fp> {
+    fn helper_decode_attempt_3_4(&self, u: P::BaseField, y: P::BaseField) -> Option<P::BaseField> {
         let step_1 = u.square() * &self.minus_3 + &self.minus_4_times_b_plus_1;
 
         if self.compute_legendre_symbol(step_1) == -1 {
@@ -314,15 +568,38 @@ impl<P: BnParameters> Encoder<P> {
         }
     }
 
-    pub fn decode_without_hints(&self, p: G1Affine<P>) -> [Option<P::Fp>; 4] {
+    /// Compare two points, ignoring the coordinates of the point at infinity.
+    fn is_same_point(p1: &GroupAffine<P>, p2: &GroupAffine<P>) -> bool {
+        p1.infinity == p2.infinity && (p1.infinity || (p1.x == p2.x && p1.y == p2.y))
+    }
+
+    /// Find the exceptional input that is encoded as the point with the hint, if any.
+    fn find_exceptional_input(&self, p: &GroupAffine<P>, hint: DecodeHint) -> Option<P::BaseField> {
+        self.exceptional_inputs
+            .iter()
+            .find(|(_, point, exceptional_hint)| {
+                *exceptional_hint == hint && Self::is_same_point(point, p)
+            })
+            .map(|(val, _, _)| *val)
+    }
+
+    pub fn decode_without_hints(&self, p: GroupAffine<P>) -> [Option<P::BaseField>; 4] {
         let mut res = [None; 4];
 
+        // the point at infinity is only the output of the exceptional inputs
+        if p.infinity {
+            for (i, r) in res.iter_mut().enumerate() {
+                *r = self.find_exceptional_input(&p, i as u8 + 1);
+            }
+            return res;
+        }
+
         // attempt 1: x = x1
         let attempt_1_t = self.helper_decode_attempt_1_2(p.x, p.y);
         res[0] = attempt_1_t;
 
         // attempt 2: x = x2
-        let attempt_2_t = self.helper_decode_attempt_1_2((p.x + &P::Fp::one()).neg(), p.y);
+        let attempt_2_t = self.helper_decode_attempt_1_2((p.x + &P::BaseField::one()).neg(), p.y);
         res[1] = attempt_2_t;
 
         // attempt 3: x = x3, u = sqrt{x3 - 1}
@@ -330,7 +607,7 @@ impl<P: BnParameters> Encoder<P> {
         let mut attempt_3_t = None;
         let mut attempt_4_t = None;
 
-        let x_minus_one = p.x - &P::Fp::one();
+        let x_minus_one = p.x - &P::BaseField::one();
         if self.compute_legendre_symbol(x_minus_one) != -1 {
             let sqrt_x_minus_one = self.compute_square_root(x_minus_one);
 
@@ -345,72 +622,204 @@ impl<P: BnParameters> Encoder<P> {
         res[2] = attempt_3_t;
         res[3] = attempt_4_t;
 
+        for (i, r) in res.iter_mut().enumerate() {
+            if let Some(val) = self.find_exceptional_input(&p, i as u8 + 1) {
+                *r = Some(val);
+            }
+        }
+
         res
     }
 
-    pub fn decode_with_hints(&self, p: G1Affine<P>, hint: u8) -> P::Fp {
+    /// Validate the point and the hint, and decode the exceptional inputs.
+    /// Returns `None` for the points that need the rest of the decoding.
+    fn decode_exceptional(
+        &self,
+        p: &GroupAffine<P>,
+        hint: u8,
+    ) -> Result<Option<P::BaseField>, DecodeError> {
+        if !(1..=4).contains(&hint) {
+            return Err(DecodeError::InvalidHint(hint));
+        }
+        if !p.is_on_curve() {
+            return Err(DecodeError::PointNotOnCurve);
+        }
+
+        if let Some(val) = self.find_exceptional_input(p, hint) {
+            return Ok(Some(val));
+        }
+        if p.infinity {
+            return Err(DecodeError::NoPreimage);
+        }
+
+        Ok(None)
+    }
+
+    /// The x coordinate as x1 for the hints 1 and 2, since x2 = -1 - x1.
+    fn x1_for_hint(p: &GroupAffine<P>, hint: u8) -> P::BaseField {
         if hint == 1 {
-            self.helper_decode_attempt_1_2(p.x, p.y).unwrap()
-        } else if hint == 2 {
-            self.helper_decode_attempt_1_2((p.x + &P::Fp::one()).neg(), p.y)
-                .unwrap()
+            p.x
         } else {
-            let x_minus_one = p.x - &P::Fp::one();
-            let sqrt_x_minus_one = self.compute_square_root(x_minus_one);
+            (p.x + &P::BaseField::one()).neg()
+        }
+    }
 
-            if hint == 3 {
-                if sqrt_x_minus_one.cmp(&self.q_minus_1_div_2) == Ordering::Less {
-                    self.helper_decode_attempt_3_4(sqrt_x_minus_one, p.y)
-                        .unwrap()
-                } else {
-                    self.helper_decode_attempt_3_4(sqrt_x_minus_one.neg(), p.y)
-                        .unwrap()
+    fn decode_attempt_3_4(&self, p: &GroupAffine<P>, hint: u8) -> Option<P::BaseField> {
+        let x_minus_one = p.x - &P::BaseField::one();
+        if self.compute_legendre_symbol(x_minus_one) == -1 {
+            return None;
+        }
+        let sqrt_x_minus_one = self.compute_square_root(x_minus_one);
+
+        // hint 3 takes the positive u, and hint 4 takes the negative u
+        let is_positive = sqrt_x_minus_one.cmp(&self.q_minus_1_div_2) == Ordering::Less;
+        if is_positive == (hint == 3) {
+            self.helper_decode_attempt_3_4(sqrt_x_minus_one, p.y)
+        } else {
+            self.helper_decode_attempt_3_4(sqrt_x_minus_one.neg(), p.y)
+        }
+    }
+
+    /// Decode the point with the hint returned by [`Self::encode`].
+    pub fn decode_with_hints(
+        &self,
+        p: GroupAffine<P>,
+        hint: u8,
+    ) -> Result<P::BaseField, DecodeError> {
+        if let Some(val) = self.decode_exceptional(&p, hint)? {
+            return Ok(val);
+        }
+
+        let res = match hint {
+            1 | 2 => self.helper_decode_attempt_1_2(Self::x1_for_hint(&p, hint), p.y),
+            _ => self.decode_attempt_3_4(&p, hint),
+        };
+
+        res.ok_or(DecodeError::NoPreimage)
+    }
+
+    /// Decode the points with their hints as [`Self::decode_with_hints`] does,
+    /// with the inversions of the hints 1 and 2 shared across the batch.
+    pub fn decode_batch(
+        &self,
+        inputs: &[(GroupAffine<P>, DecodeHint)],
+    ) -> Vec<Result<P::BaseField, DecodeError>> {
+        let res: Vec<Result<Option<P::BaseField>, DecodeError>> = inputs
+            .iter()
+            .map(|(p, hint)| self.decode_exceptional(p, *hint))
+            .collect();
+
+        // zero stands for a failed inversion, and for the inputs that do not need one
+        let mut step_1: Vec<P::BaseField> = res
+            .iter()
+            .zip(inputs.iter())
+            .map(|(r, (p, hint))| match r {
+                Ok(None) if *hint <= 2 => {
+                    (Self::x1_for_hint(p, *hint) - &self.sqrt_minus_3_minus_1_div_2).neg()
                 }
-            } else {
-                if sqrt_x_minus_one.cmp(&self.q_minus_1_div_2) == Ordering::Less {
-                    self.helper_decode_attempt_3_4(sqrt_x_minus_one.neg(), p.y)
-                        .unwrap()
+                _ => P::BaseField::zero(),
+            })
+            .collect();
+        batch_inversion(&mut step_1);
+
+        let mut step_2: Vec<P::BaseField> = step_1
+            .iter()
+            .map(|step_1| {
+                if step_1.is_zero() {
+                    P::BaseField::zero()
                 } else {
-                    self.helper_decode_attempt_3_4(sqrt_x_minus_one, p.y)
-                        .unwrap()
+                    *step_1 * &self.sqrt_minus_3 + &self.minus_one
                 }
-            }
-        }
+            })
+            .collect();
+        batch_inversion(&mut step_2);
+
+        res.into_iter()
+            .zip(inputs.iter())
+            .zip(step_2)
+            .map(|((r, (p, hint)), step_2)| {
+                if let Some(val) = r? {
+                    return Ok(val);
+                }
+
+                let res = match hint {
+                    1 | 2 if step_2.is_zero() => None,
+                    1 | 2 => self.helper_decode_attempt_1_2_with_inverses(step_2, p.y),
+                    _ => self.decode_attempt_3_4(p, *hint),
+                };
+
+                res.ok_or(DecodeError::NoPreimage)
+            })
+            .collect()
     }
-}
 
-impl<P: BnParameters> Drop for Encoder<P> {
-    fn drop(&mut self) {
-        unsafe { gmp::mpz_clear(&mut self.q) }
+    /// Decode the point with the hint as [`Self::decode_with_hints`], and check that the result
+    /// is encoded back to the same point and hint, which rejects points and hints that [`Self::encode`]
+    /// never outputs together.
+    pub fn decode_with_hints_and_check<R: RngCore>(
+        &self,
+        p: GroupAffine<P>,
+        hint: u8,
+        rng: &mut R,
+    ) -> Result<P::BaseField, DecodeError> {
+        let val = self.decode_with_hints(p, hint)?;
+
+        let (p_reencoded, hint_reencoded) = self.encode(val, rng);
+        if hint_reencoded == hint && Self::is_same_point(&p_reencoded, &p) {
+            Ok(val)
+        } else {
+            Err(DecodeError::ReencodingMismatch)
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use crate::curve_bn254::g1::Parameters as Bn254G1Parameters;
+    use crate::curve_bn254::{Fq as Bn254Fq, Fr as Bn254Fr};
+    use crate::curve_bn446::g1::Parameters as Bn446G1Parameters;
     use crate::curve_bn446::Parameters as Bn446Parameters;
-    use crate::message_encoding::Encoder;
-    use ark_ec::bn::BnParameters;
-    use ark_ff::{LegendreSymbol, SquareRootField, Zero};
-    use ark_std::mem::MaybeUninit;
+    #[cfg(feature = "std")]
+    use crate::message_encoding::{bn254_encoder, bn446_encoder};
+    use crate::message_encoding::{DecodeError, Encoder, EncoderError};
+    use ark_ec::bn::{BnParameters, G1Affine};
+    use ark_ec::short_weierstrass_jacobian::GroupAffine;
+    use ark_ec::{ModelParameters, SWModelParameters};
+    use ark_ff::biginteger::BigInteger256;
+    use ark_ff::fields::{FftParameters, Fp256, Fp256Parameters, FpParameters};
+    use ark_ff::{field_new, Field, LegendreSymbol, One, PrimeField, SquareRootField, Zero};
+    use ark_std::ops::Neg;
     use ark_std::ops::ShlAssign;
     use ark_std::str::FromStr;
     use ark_std::UniformRand;
-    use gmp_mpfr_sys::gmp;
     use num_bigint::BigUint;
 
+    #[cfg(feature = "gmp")]
+    use ark_std::mem::MaybeUninit;
+    #[cfg(feature = "gmp")]
+    use gmp_mpfr_sys::gmp;
+
     const REPETITIONS: u64 = 100;
 
     #[test]
     fn test_precomputation() {
-        let encoder = Encoder::<Bn446Parameters>::new();
+        let encoder = Encoder::<Bn446G1Parameters>::new().unwrap();
 
         // q = 102211695604069718983520304652693874995639508460729604902280098199792736381528662976886082950231100101353700265360419596271313339023463
+        #[cfg(feature = "gmp")]
         unsafe {
             let mut expected_q = MaybeUninit::uninit();
             gmp::mpz_init_set_str(expected_q.as_mut_ptr(), "102211695604069718983520304652693874995639508460729604902280098199792736381528662976886082950231100101353700265360419596271313339023463\x00".as_ptr() as *const i8, 10);
             let mut expected_q = expected_q.assume_init();
+            let limbs = <Bn446G1Parameters as ModelParameters>::BaseField::characteristic();
+            let mut q = MaybeUninit::uninit();
+            let q = gmp::mpz_roinit_n(
+                q.as_mut_ptr(),
+                limbs.as_ptr() as *const gmp::limb_t,
+                limbs.len() as gmp::size_t,
+            );
 
-            assert_eq!(gmp::mpz_cmp(&expected_q, &encoder.q), 0);
+            assert_eq!(gmp::mpz_cmp(&expected_q, q), 0);
 
             gmp::mpz_clear(&mut expected_q);
         }
@@ -463,7 +872,7 @@ mod test {
     #[test]
     fn test_square_root() {
         let mut rng = ark_std::test_rng();
-        let encoder = Encoder::<Bn446Parameters>::new();
+        let encoder = Encoder::<Bn446G1Parameters>::new().unwrap();
 
         for _ in 0..REPETITIONS {
             let mut a = <Bn446Parameters as BnParameters>::Fp::rand(&mut rng);
@@ -477,7 +886,7 @@ mod test {
     #[test]
     fn test_legendre_symbol() {
         let mut rng = ark_std::test_rng();
-        let encoder = Encoder::<Bn446Parameters>::new();
+        let encoder = Encoder::<Bn446G1Parameters>::new().unwrap();
 
         for _ in 0..REPETITIONS {
             let a = <Bn446Parameters as BnParameters>::Fp::rand(&mut rng);
@@ -497,7 +906,7 @@ mod test {
     #[test]
     fn test_encoding() {
         let mut rng = ark_std::test_rng();
-        let encoder = Encoder::<Bn446Parameters>::new();
+        let encoder = Encoder::<Bn446G1Parameters>::new().unwrap();
 
         let (p1, h1) = encoder.encode(<Bn446Parameters as BnParameters>::Fp::from(3u64), &mut rng);
         assert_eq!(BigUint::from_str("36716321155346290056326261881547168995119143630874288596213978910008386805926472362186816803935134015").unwrap(), p1.x.into());
@@ -518,7 +927,7 @@ mod test {
     #[test]
     fn test_decoding_simple() {
         let mut rng = ark_std::test_rng();
-        let encoder = Encoder::<Bn446Parameters>::new();
+        let encoder = Encoder::<Bn446G1Parameters>::new().unwrap();
 
         let (p1, _) = encoder.encode(<Bn446Parameters as BnParameters>::Fp::from(3u64), &mut rng);
         let (p2, _) = encoder.encode(<Bn446Parameters as BnParameters>::Fp::from(4u64), &mut rng);
@@ -549,11 +958,433 @@ mod test {
         for _ in 0..REPETITIONS {
             let t = <Bn446Parameters as BnParameters>::Fp::rand(&mut rng);
 
-            let encoder = Encoder::<Bn446Parameters>::new();
+            let encoder = Encoder::<Bn446G1Parameters>::new().unwrap();
             let (p, h) = encoder.encode(t, &mut rng);
 
-            let t_recovered = encoder.decode_with_hints(p, h);
+            let t_recovered = encoder.decode_with_hints(p, h).unwrap();
             assert_eq!(t, t_recovered);
         }
     }
+
+    fn check_exceptional_inputs<P: SWModelParameters>(num_exceptional_inputs: usize)
+    where
+        P::BaseField: PrimeField,
+    {
+        let mut rng = ark_std::test_rng();
+        let encoder = Encoder::<P>::new().unwrap();
+
+        // t = 0 and the roots of t^2 = -(1 + b)
+        let mut inputs = vec![P::BaseField::zero()];
+        if let Some(root) = encoder.b_plus_one.neg().sqrt() {
+            inputs.push(root);
+            inputs.push(root.neg());
+        }
+        assert_eq!(inputs.len(), num_exceptional_inputs);
+        assert_eq!(encoder.exceptional_inputs.len(), num_exceptional_inputs);
+
+        for t in inputs.iter() {
+            let (p, h) = encoder.encode(*t, &mut rng);
+            assert!(p.is_on_curve());
+
+            assert_eq!(encoder.decode_with_hints(p, h), Ok(*t));
+            assert_eq!(encoder.decode_with_hints_and_check(p, h, &mut rng), Ok(*t));
+            assert_eq!(encoder.decode_without_hints(p)[h as usize - 1], Some(*t));
+        }
+    }
+
+    #[test]
+    fn test_exceptional_inputs() {
+        // 1 + b = 3 is not a square for BN254, so t = 0 and t = +-sqrt(-3) go to the point at infinity
+        check_exceptional_inputs::<Bn254G1Parameters>(3);
+        // 1 + b = 258 is a square for BN446, and only t = 0 is exceptional
+        check_exceptional_inputs::<Bn446G1Parameters>(1);
+        // 1 + b = 5 is not a square for BLS12-381
+        check_exceptional_inputs::<ark_bls12_381::g1::Parameters>(3);
+
+        let mut rng = ark_std::test_rng();
+        let encoder = Encoder::<Bn446G1Parameters>::new().unwrap();
+
+        let (p, h) = encoder.encode(<Bn446Parameters as BnParameters>::Fp::zero(), &mut rng);
+        assert_eq!(p.x, encoder.sqrt_minus_3_minus_1_div_2);
+        assert_eq!(p.y.square(), encoder.b_plus_one);
+        assert_eq!(h, 1);
+
+        // x = 1 is on the curve, and x - 1 = 0 should not break the decoding
+        let one = <Bn446Parameters as BnParameters>::Fp::one();
+        let p = G1Affine::<Bn446Parameters>::new(one, encoder.b_plus_one.sqrt().unwrap(), false);
+        assert!(p.is_on_curve());
+        let _ = encoder.decode_without_hints(p);
+    }
+
+    #[test]
+    fn test_decoding_errors() {
+        let mut rng = ark_std::test_rng();
+        let encoder = Encoder::<Bn446G1Parameters>::new().unwrap();
+
+        let t = <Bn446Parameters as BnParameters>::Fp::rand(&mut rng);
+        let (p, h) = encoder.encode(t, &mut rng);
+        assert_eq!(encoder.decode_with_hints_and_check(p, h, &mut rng), Ok(t));
+
+        assert_eq!(
+            encoder.decode_with_hints(p, 0),
+            Err(DecodeError::InvalidHint(0))
+        );
+        assert_eq!(
+            encoder.decode_with_hints(p, 5),
+            Err(DecodeError::InvalidHint(5))
+        );
+
+        let mut p_corrupted = p;
+        p_corrupted.y += &<Bn446Parameters as BnParameters>::Fp::one();
+        assert_eq!(
+            encoder.decode_with_hints(p_corrupted, h),
+            Err(DecodeError::PointNotOnCurve)
+        );
+
+        // the point at infinity is only the output of t = 0, with hint 1, for BN446
+        let zero = G1Affine::<Bn446Parameters>::zero();
+        assert_eq!(
+            encoder.decode_with_hints(zero, 1),
+            Err(DecodeError::NoPreimage)
+        );
+
+        let mut num_no_preimage = 0;
+        let mut num_mismatch = 0;
+        for _ in 0..REPETITIONS {
+            let t = <Bn446Parameters as BnParameters>::Fp::rand(&mut rng);
+            let (p, h) = encoder.encode(t, &mut rng);
+
+            for wrong_hint in (1..=4).filter(|hint| *hint != h) {
+                match encoder.decode_with_hints(p, wrong_hint) {
+                    Err(e) => {
+                        assert_eq!(e, DecodeError::NoPreimage);
+                        num_no_preimage += 1;
+                    }
+                    Ok(t_other) => {
+                        // decoding with a wrong hint may find another preimage, which is
+                        // either a genuine preimage of the point or encoded elsewhere
+                        match encoder.decode_with_hints_and_check(p, wrong_hint, &mut rng) {
+                            Ok(t_checked) => assert_eq!(t_checked, t_other),
+                            Err(e) => {
+                                assert_eq!(e, DecodeError::ReencodingMismatch);
+                                num_mismatch += 1;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        assert!(num_no_preimage > 0);
+        assert!(num_mismatch > 0);
+    }
+
+    fn check_encoding_rand<P: SWModelParameters>()
+    where
+        P::BaseField: PrimeField,
+    {
+        let mut rng = ark_std::test_rng();
+        let encoder = Encoder::<P>::new().unwrap();
+
+        for _ in 0..REPETITIONS {
+            let t = P::BaseField::rand(&mut rng);
+            let (p, h) = encoder.encode(t, &mut rng);
+            assert!(p.is_on_curve());
+
+            assert_eq!(encoder.decode_with_hints(p, h), Ok(t));
+        }
+    }
+
+    #[test]
+    fn test_other_curves() {
+        check_encoding_rand::<Bn254G1Parameters>();
+        check_encoding_rand::<ark_bls12_381::g1::Parameters>();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_static_encoders() {
+        fn assert_send_sync<T: Send + Sync + Clone>() {}
+        assert_send_sync::<Encoder<Bn254G1Parameters>>();
+        assert_send_sync::<Encoder<Bn446G1Parameters>>();
+
+        assert!(ark_std::ptr::eq(bn254_encoder(), bn254_encoder()));
+        assert!(ark_std::ptr::eq(bn446_encoder(), bn446_encoder()));
+
+        let encoder = Encoder::<Bn446G1Parameters>::new().unwrap();
+        let cloned = bn446_encoder().clone();
+        assert_eq!(cloned.sqrt_minus_3, encoder.sqrt_minus_3);
+        assert_eq!(cloned.square_root_pow, encoder.square_root_pow);
+        assert_eq!(cloned.exceptional_inputs, encoder.exceptional_inputs);
+
+        // the static encoder is shared across threads
+        let results: Vec<_> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..4u64)
+                .map(|i| {
+                    s.spawn(move || {
+                        let mut rng = ark_std::test_rng();
+                        let t = Bn254Fq::from(i + 1);
+                        let (p, h) = bn254_encoder().encode(t, &mut rng);
+                        (t, bn254_encoder().decode_with_hints(p, h))
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        for (t, res) in results {
+            assert_eq!(res, Ok(t));
+        }
+    }
+
+    fn check_batch<P: SWModelParameters>()
+    where
+        P::BaseField: PrimeField,
+    {
+        let mut rng = ark_std::test_rng();
+        let encoder = Encoder::<P>::new().unwrap();
+
+        let mut vals: Vec<P::BaseField> = (0..REPETITIONS)
+            .map(|_| P::BaseField::rand(&mut rng))
+            .collect();
+        vals.extend(encoder.exceptional_inputs.iter().map(|(val, _, _)| *val));
+
+        let encoded = encoder.encode_batch(&vals, &mut rng);
+        for (val, res) in vals.iter().zip(encoded.iter()) {
+            assert_eq!(encoder.encode(*val, &mut rng), *res);
+        }
+
+        // the correct hints, all the other hints, and the invalid inputs
+        let mut inputs = Vec::new();
+        for (p, _) in encoded.iter() {
+            for hint in 0..=5 {
+                inputs.push((*p, hint));
+            }
+        }
+        let mut p_corrupted = encoded[0].0;
+        p_corrupted.y += &P::BaseField::one();
+        inputs.push((p_corrupted, encoded[0].1));
+        inputs.push((GroupAffine::<P>::zero(), 1));
+
+        let decoded = encoder.decode_batch(&inputs);
+        for ((p, hint), res) in inputs.iter().zip(decoded.iter()) {
+            assert_eq!(encoder.decode_with_hints(*p, *hint), *res);
+        }
+        for ((val, (_, hint)), res) in vals.iter().zip(encoded.iter()).zip(decoded.chunks(6)) {
+            assert_eq!(res[*hint as usize], Ok(*val));
+        }
+
+        assert!(encoder.encode_batch(&[], &mut rng).is_empty());
+        assert!(encoder.decode_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_batch() {
+        check_batch::<Bn254G1Parameters>();
+        check_batch::<Bn446G1Parameters>();
+    }
+
+    /// y^2 = x^3 + x + 2 over the base field of BN254.
+    struct NonZeroAParameters;
+
+    impl ModelParameters for NonZeroAParameters {
+        type BaseField = Bn254Fq;
+        type ScalarField = Bn254Fr;
+    }
+
+    impl SWModelParameters for NonZeroAParameters {
+        const COEFF_A: Bn254Fq = field_new!(Bn254Fq, "1");
+        const COEFF_B: Bn254Fq = field_new!(Bn254Fq, "2");
+        const COFACTOR: &'static [u64] = &[0x1];
+        const COFACTOR_INV: Bn254Fr = field_new!(Bn254Fr, "1");
+        const AFFINE_GENERATOR_COEFFS: (Bn254Fq, Bn254Fq) =
+            (field_new!(Bn254Fq, "0"), field_new!(Bn254Fq, "0"));
+    }
+
+    /// y^2 = x^3 + 2 over the scalar field of BN254, which is 1 mod 4 with two-adicity 2.
+    struct OneModFourParameters;
+
+    impl ModelParameters for OneModFourParameters {
+        type BaseField = Bn254Fr;
+        type ScalarField = Bn254Fr;
+    }
+
+    impl SWModelParameters for OneModFourParameters {
+        const COEFF_A: Bn254Fr = field_new!(Bn254Fr, "0");
+        const COEFF_B: Bn254Fr = field_new!(Bn254Fr, "2");
+        const COFACTOR: &'static [u64] = &[0x1];
+        const COFACTOR_INV: Bn254Fr = field_new!(Bn254Fr, "1");
+        const AFFINE_GENERATOR_COEFFS: (Bn254Fr, Bn254Fr) =
+            (field_new!(Bn254Fr, "0"), field_new!(Bn254Fr, "0"));
+    }
+
+    /// y^2 = x^3 - 1 over the base field of BN254.
+    struct MinusOneBParameters;
+
+    impl ModelParameters for MinusOneBParameters {
+        type BaseField = Bn254Fq;
+        type ScalarField = Bn254Fr;
+    }
+
+    impl SWModelParameters for MinusOneBParameters {
+        const COEFF_A: Bn254Fq = field_new!(Bn254Fq, "0");
+        const COEFF_B: Bn254Fq = field_new!(Bn254Fq, "-1");
+        const COFACTOR: &'static [u64] = &[0x1];
+        const COFACTOR_INV: Bn254Fr = field_new!(Bn254Fr, "1");
+        const AFFINE_GENERATOR_COEFFS: (Bn254Fq, Bn254Fq) =
+            (field_new!(Bn254Fq, "0"), field_new!(Bn254Fq, "0"));
+    }
+
+    /// The prime field of MODULUS = 2^253 + 53187, which is 2 mod 3, so -3 is not a square.
+    type TwoModThreeFq = Fp256<TwoModThreeFqParameters>;
+
+    struct TwoModThreeFqParameters;
+
+    impl Fp256Parameters for TwoModThreeFqParameters {}
+    impl FftParameters for TwoModThreeFqParameters {
+        type BigInt = BigInteger256;
+
+        const TWO_ADICITY: u32 = 1;
+
+        /// two_adic_root_of_unity = -1, written as -R (in the Montgomery representation) = 425496
+        #[rustfmt::skip]
+        const TWO_ADIC_ROOT_OF_UNITY: BigInteger256 = BigInteger256([
+            425496u64,
+            0u64,
+            0u64,
+            0u64,
+        ]);
+    }
+    impl FpParameters for TwoModThreeFqParameters {
+        /// MODULUS = 14474011154664524427946373126085988481658748083205070504932198000989141258179
+        #[rustfmt::skip]
+        const MODULUS: BigInteger256 = BigInteger256([
+            53187u64,
+            0u64,
+            0u64,
+            2305843009213693952u64,
+        ]);
+
+        const MODULUS_BITS: u32 = 254;
+
+        const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+        const REPR_SHAVE_BITS: u32 = 2;
+
+        /// R = pow(2, 256) % MODULUS
+        #[rustfmt::skip]
+        const R: BigInteger256 = BigInteger256([
+            18446744073709179307u64,
+            18446744073709551615u64,
+            18446744073709551615u64,
+            2305843009213693951u64,
+        ]);
+
+        /// R2 = R * R % MODULUS = 181046846016
+        #[rustfmt::skip]
+        const R2: BigInteger256 = BigInteger256([
+            181046846016u64,
+            0u64,
+            0u64,
+            0u64,
+        ]);
+
+        /// INV = (-MODULUS) ^ {-1} % pow(2, 64)
+        const INV: u64 = 17224521987189303061u64;
+
+        /// GENERATOR = 2, which generates the group since (MODULUS - 1) / 2 is a prime,
+        /// written as 2 * R (in the Montgomery representation)
+        #[rustfmt::skip]
+        const GENERATOR: BigInteger256 = BigInteger256([
+            18446744073708753811u64,
+            18446744073709551615u64,
+            18446744073709551615u64,
+            2305843009213693951u64,
+        ]);
+
+        /// (MODULUS - 1) / 2
+        #[rustfmt::skip]
+        const MODULUS_MINUS_ONE_DIV_TWO: BigInteger256 = BigInteger256([
+            26593u64,
+            0u64,
+            0u64,
+            1152921504606846976u64,
+        ]);
+
+        /// T = (MODULUS - 1) / 2
+        #[rustfmt::skip]
+        const T: BigInteger256 = BigInteger256([
+            26593u64,
+            0u64,
+            0u64,
+            1152921504606846976u64,
+        ]);
+
+        /// (T - 1) / 2
+        #[rustfmt::skip]
+        const T_MINUS_ONE_DIV_TWO: BigInteger256 = BigInteger256([
+            13296u64,
+            0u64,
+            0u64,
+            576460752303423488u64,
+        ]);
+    }
+
+    /// y^2 = x^3 + 2 over a field where -3 is not a square.
+    struct MinusThreeNotSquareParameters;
+
+    impl ModelParameters for MinusThreeNotSquareParameters {
+        type BaseField = TwoModThreeFq;
+        type ScalarField = Bn254Fr;
+    }
+
+    impl SWModelParameters for MinusThreeNotSquareParameters {
+        const COEFF_A: TwoModThreeFq = field_new!(TwoModThreeFq, "0");
+        const COEFF_B: TwoModThreeFq = field_new!(TwoModThreeFq, "2");
+        const COFACTOR: &'static [u64] = &[0x1];
+        const COFACTOR_INV: Bn254Fr = field_new!(Bn254Fr, "1");
+        const AFFINE_GENERATOR_COEFFS: (TwoModThreeFq, TwoModThreeFq) = (
+            field_new!(TwoModThreeFq, "0"),
+            field_new!(TwoModThreeFq, "0"),
+        );
+    }
+
+    #[test]
+    fn test_unsupported_parameters() {
+        assert_eq!(
+            Encoder::<NonZeroAParameters>::new().err(),
+            Some(EncoderError::NonZeroCoefficientA)
+        );
+        assert_eq!(
+            Encoder::<MinusOneBParameters>::new().err(),
+            Some(EncoderError::UnsupportedCoefficientB)
+        );
+
+        // the field is correct, and -3 is a non-square in it
+        assert_eq!(
+            TwoModThreeFq::from(3u64) * &TwoModThreeFq::from(5u64),
+            TwoModThreeFq::from(15u64)
+        );
+        assert_eq!(
+            TwoModThreeFq::from(3u64).neg().legendre(),
+            LegendreSymbol::QuadraticNonResidue
+        );
+        assert_eq!(
+            Encoder::<MinusThreeNotSquareParameters>::new().err(),
+            Some(EncoderError::MinusThreeNotSquare)
+        );
+    }
+
+    #[test]
+    fn test_one_mod_four() {
+        let mut rng = ark_std::test_rng();
+        let encoder = Encoder::<OneModFourParameters>::new().unwrap();
+        assert_eq!(encoder.two_adicity, 2);
+
+        for _ in 0..REPETITIONS {
+            let a = Bn254Fr::rand(&mut rng).square();
+            assert_eq!(encoder.compute_square_root(a).square(), a);
+        }
+
+        check_encoding_rand::<OneModFourParameters>();
+        check_batch::<OneModFourParameters>();
+    }
 }
@@ -0,0 +1,341 @@
+use crate::message_encoding::hybrid::{pad_message, unpadded_len, HybridEncoder};
+use ark_ec::bn::{BnParameters, G1Affine};
+use ark_std::rand::RngCore;
+use ark_std::vec::Vec;
+use std::io::{self, Read};
+use std::iter::Peekable;
+
+/// The error of an earlier call, which every later call of a failed adapter returns again.
+#[derive(Clone)]
+struct Failure {
+    kind: io::ErrorKind,
+    message: String,
+}
+
+impl Failure {
+    fn record(err: &io::Error) -> Self {
+        Self {
+            kind: err.kind(),
+            message: err.to_string(),
+        }
+    }
+
+    fn to_error(&self) -> io::Error {
+        io::Error::new(self.kind, self.message.clone())
+    }
+}
+
+/// An iterator over the blocks of points that encode a message read from a reader, one block of
+/// [`HybridEncoder::get_capacity`] bytes at a time, so only one block is in memory. The points of a block
+/// are encoded in parallel under the `parallel` feature.
+///
+/// The reader is read to its end, so the length of the message does not need to be known up front.
+/// The blocks are the same as those of [`HybridEncoder::encode_message`] for the bytes of the reader,
+/// so they can be decoded by [`HybridEncoder::decode_message`] or by [`StreamDecoder`].
+/// After an error, every later call returns the error again.
+pub struct StreamEncoder<'a, P: BnParameters + Sync, R: Read, G: RngCore> {
+    encoder: &'a HybridEncoder<P>,
+    reader: R,
+    rng: &'a mut G,
+    buffer: Vec<u8>,
+    finished: bool,
+    failure: Option<Failure>,
+}
+
+impl<'a, P: BnParameters + Sync, R: Read, G: RngCore> StreamEncoder<'a, P, R, G> {
+    /// Encode the message that the reader holds up to its end.
+    pub fn new(encoder: &'a HybridEncoder<P>, reader: R, rng: &'a mut G) -> Self {
+        Self {
+            encoder,
+            reader,
+            rng,
+            buffer: Vec::with_capacity(encoder.get_capacity()),
+            finished: false,
+            failure: None,
+        }
+    }
+
+    /// Fill the buffer with the bytes of the message for the next block, and the padding in the last block.
+    fn fill_buffer(&mut self) -> io::Result<()> {
+        let capacity = self.encoder.get_capacity();
+
+        self.buffer.clear();
+        self.buffer.resize(capacity, 0);
+        let mut len = 0;
+        while len < capacity {
+            match self.reader.read(&mut self.buffer[len..]) {
+                Ok(0) => break,
+                Ok(n) => len += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        self.buffer.truncate(len);
+
+        // a full block is followed by another one, which has only the padding if the reader has ended
+        if len < capacity {
+            pad_message(&mut self.buffer, capacity);
+            self.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, P: BnParameters + Sync, R: Read, G: RngCore> Iterator for StreamEncoder<'a, P, R, G> {
+    type Item = io::Result<Vec<G1Affine<P>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(failure) = &self.failure {
+            return Some(Err(failure.to_error()));
+        }
+        if self.finished {
+            return None;
+        }
+
+        if let Err(err) = self.fill_buffer() {
+            self.failure = Some(Failure::record(&err));
+            return Some(Err(err));
+        }
+
+        Some(Ok(self.encoder.encode(&self.buffer, self.rng)))
+    }
+}
+
+/// A reader of the message that a sequence of blocks from [`StreamEncoder`] or
+/// [`HybridEncoder::encode_message`] encodes.
+///
+/// The padding is in the last block, so the decoder looks one block ahead to find it, and holds at most
+/// two blocks at a time. The corrupted blocks, the malformed padding, and the empty sequence are reported
+/// as [`io::ErrorKind::InvalidData`]. After an error, every later read returns the error again.
+///
+/// The framing is not an authentication: blocks dropped from the end are only detected when the new
+/// last block does not end like a padding, and the blocks themselves must be authenticated separately.
+pub struct StreamDecoder<'a, P: BnParameters + Sync, I: Iterator<Item = Vec<G1Affine<P>>>> {
+    encoder: &'a HybridEncoder<P>,
+    blocks: Peekable<I>,
+    current: Vec<u8>,
+    position: usize,
+    finished: bool,
+    failure: Option<Failure>,
+}
+
+impl<'a, P: BnParameters + Sync, I: Iterator<Item = Vec<G1Affine<P>>>> StreamDecoder<'a, P, I> {
+    pub fn new(encoder: &'a HybridEncoder<P>, blocks: I) -> Self {
+        Self {
+            encoder,
+            blocks: blocks.peekable(),
+            current: Vec::new(),
+            position: 0,
+            finished: false,
+            failure: None,
+        }
+    }
+
+    fn invalid_data<E>(err: E) -> io::Error
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+
+    /// Decode the next block, and keep its bytes of the message.
+    fn advance(&mut self) -> io::Result<()> {
+        let block = self
+            .blocks
+            .next()
+            .ok_or_else(|| Self::invalid_data("the stream has no blocks"))?;
+        let mut bytes = self.encoder.decode(&block).map_err(Self::invalid_data)?;
+
+        // the last block ends with the padding
+        if self.blocks.peek().is_none() {
+            let len =
+                unpadded_len(&bytes, self.encoder.get_capacity()).map_err(Self::invalid_data)?;
+            bytes.truncate(len);
+            self.finished = true;
+        }
+
+        self.current = bytes;
+        self.position = 0;
+
+        Ok(())
+    }
+}
+
+impl<'a, P: BnParameters + Sync, I: Iterator<Item = Vec<G1Affine<P>>>> Read
+    for StreamDecoder<'a, P, I>
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(failure) = &self.failure {
+            return Err(failure.to_error());
+        }
+
+        while self.position == self.current.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            if let Err(err) = self.advance() {
+                self.failure = Some(Failure::record(&err));
+                return Err(err);
+            }
+        }
+
+        let len = buf.len().min(self.current.len() - self.position);
+        buf[..len].copy_from_slice(&self.current[self.position..self.position + len]);
+        self.position += len;
+
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::curve_bn254::Parameters as Bn254Parameters;
+    use crate::message_encoding::hybrid::HybridEncoder;
+    use crate::message_encoding::stream::{StreamDecoder, StreamEncoder};
+    use ark_std::rand::RngCore;
+    use std::io::{self, Cursor, Read};
+
+    /// A reader that returns at most 5 bytes per call.
+    struct Pieces<R: Read>(R);
+
+    impl<R: Read> Read for Pieces<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(5);
+            self.0.read(&mut buf[..len])
+        }
+    }
+
+    /// A reader that always fails.
+    struct Failing;
+
+    impl Read for Failing {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "the reader failed",
+            ))
+        }
+    }
+
+    #[test]
+    fn test_stream_rand() {
+        let mut rng = ark_std::test_rng();
+        let encoder = HybridEncoder::<Bn254Parameters>::new();
+        let capacity = encoder.get_capacity();
+        let block_len = encoder.get_block_len();
+
+        // the message fills the first block up to the padding, up to the end, and past the end
+        for len in [0, 1, capacity - 1, capacity, 2 * capacity + 5] {
+            let mut data = vec![0u8; len];
+            rng.fill_bytes(&mut data[..]);
+
+            let blocks = StreamEncoder::new(&encoder, Cursor::new(&data), &mut rng)
+                .collect::<io::Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(blocks.len(), (len + 1).div_ceil(capacity));
+
+            // a reader that returns a few bytes at a time gives the same blocks
+            let pieces = Pieces(Cursor::new(&data));
+            let blocks_in_pieces = StreamEncoder::new(&encoder, pieces, &mut rng)
+                .collect::<io::Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(blocks_in_pieces.len(), blocks.len());
+            let points: Vec<_> = blocks_in_pieces.concat();
+            assert_eq!(encoder.decode_message(&points).unwrap(), data);
+
+            // the blocks are the same as those of encode_message
+            let points: Vec<_> = blocks.concat();
+            assert_eq!(encoder.decode_message(&points).unwrap(), data);
+
+            let mut recovered = Vec::new();
+            StreamDecoder::new(&encoder, blocks.clone().into_iter())
+                .read_to_end(&mut recovered)
+                .unwrap();
+            assert_eq!(recovered, data);
+
+            // reading in small pieces gives the same bytes
+            let mut decoder = StreamDecoder::new(&encoder, blocks.into_iter());
+            let mut recovered = Vec::new();
+            let mut piece = [0u8; 7];
+            loop {
+                let n = decoder.read(&mut piece).unwrap();
+                if n == 0 {
+                    break;
+                }
+                recovered.extend_from_slice(&piece[..n]);
+            }
+            assert_eq!(recovered, data);
+
+            // the blocks of encode_message are read by the stream decoder
+            let points = encoder.encode_message(&data, &mut rng);
+            let blocks = points.chunks(block_len).map(|block| block.to_vec());
+            let mut recovered = Vec::new();
+            StreamDecoder::new(&encoder, blocks)
+                .read_to_end(&mut recovered)
+                .unwrap();
+            assert_eq!(recovered, data);
+        }
+    }
+
+    #[test]
+    fn test_stream_errors() {
+        let mut rng = ark_std::test_rng();
+        let encoder = HybridEncoder::<Bn254Parameters>::new();
+
+        let mut data = vec![0u8; 2 * encoder.get_capacity() + 10];
+        rng.fill_bytes(&mut data[..]);
+        let blocks = StreamEncoder::new(&encoder, Cursor::new(&data), &mut rng)
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(blocks.len(), 3);
+
+        let read = |blocks: Vec<Vec<_>>| {
+            let mut recovered = Vec::new();
+            StreamDecoder::new(&encoder, blocks.into_iter())
+                .read_to_end(&mut recovered)
+                .map_err(|err| err.kind())
+        };
+
+        assert_eq!(read(vec![]), Err(io::ErrorKind::InvalidData));
+
+        let mut truncated = blocks.clone();
+        truncated.pop();
+        assert_eq!(read(truncated), Err(io::ErrorKind::InvalidData));
+
+        let mut extended = blocks.clone();
+        extended.push(blocks[0].clone());
+        assert_eq!(read(extended), Err(io::ErrorKind::InvalidData));
+
+        // a tampered middle block fails every read after it, instead of a truncated end of the stream
+        let mut tampered = blocks.clone();
+        tampered[1].pop();
+        let mut decoder = StreamDecoder::new(&encoder, tampered.into_iter());
+        let mut recovered = Vec::new();
+        assert_eq!(
+            decoder
+                .read_to_end(&mut recovered)
+                .map_err(|err| err.kind()),
+            Err(io::ErrorKind::InvalidData)
+        );
+        let mut buf = [0u8; 16];
+        for _ in 0..3 {
+            assert_eq!(
+                decoder.read(&mut buf).map_err(|err| err.kind()),
+                Err(io::ErrorKind::InvalidData)
+            );
+        }
+
+        // a failing reader fails every later block too
+        let reader = Cursor::new(&data).chain(Failing);
+        let mut stream = StreamEncoder::new(&encoder, reader, &mut rng);
+        assert!(stream.next().unwrap().is_ok());
+        assert!(stream.next().unwrap().is_ok());
+        for _ in 0..3 {
+            assert_eq!(
+                stream.next().unwrap().map_err(|err| err.kind()),
+                Err(io::ErrorKind::BrokenPipe)
+            );
+        }
+    }
+}
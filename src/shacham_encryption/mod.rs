@@ -1,3 +1,4 @@
+use crate::elgamal_encryption::assert_permutation;
 use ark_ec::ProjectiveCurve;
 use ark_ff::PrimeField;
 use ark_std::{marker::PhantomData, vec::Vec, UniformRand};
@@ -151,6 +152,143 @@ impl<G: ProjectiveCurve> ShachamEncryption<G> {
             e: e_new,
         }
     }
+
+    /// Keep only the slots at `indices`, in the given order. The result decrypts under the
+    /// matching projection of the keys, see [`Self::project_secret_key`].
+    pub fn project(ciphertext: &ShachamCiphertext<G>, indices: &[usize]) -> ShachamCiphertext<G> {
+        let mut e = Vec::<G>::new();
+        for i in indices.iter() {
+            e.push(ciphertext.e[*i]);
+        }
+
+        ShachamCiphertext::<G> {
+            r1: ciphertext.r1,
+            r2: ciphertext.r2,
+            r3: ciphertext.r3,
+            e,
+        }
+    }
+
+    pub fn project_public_key(pk: &ShachamPublicKey<G>, indices: &[usize]) -> ShachamPublicKey<G> {
+        let mut y = Vec::<G>::new();
+        let mut z = Vec::<G>::new();
+        for i in indices.iter() {
+            y.push(pk.y[*i]);
+            z.push(pk.z[*i]);
+        }
+
+        ShachamPublicKey::<G> {
+            pp: pk.pp.clone(),
+            y,
+            z,
+        }
+    }
+
+    pub fn project_secret_key(sk: &ShachamSecretKey<G>, indices: &[usize]) -> ShachamSecretKey<G> {
+        let mut scalar_x = Vec::<G::ScalarField>::new();
+        let mut scalar_y = Vec::<G::ScalarField>::new();
+        let mut scalar_z = Vec::<G::ScalarField>::new();
+        for i in indices.iter() {
+            scalar_x.push(sk.scalar_x[*i]);
+            scalar_y.push(sk.scalar_y[*i]);
+            scalar_z.push(sk.scalar_z[*i]);
+        }
+
+        ShachamSecretKey::<G> {
+            scalar_x,
+            scalar_y,
+            scalar_z,
+        }
+    }
+
+    /// Concatenate two ciphertexts under the concatenation of their keys.
+    ///
+    /// All slots of a ciphertext share one randomness, so this only applies to ciphertexts
+    /// with the same `r1`, `r2`, `r3`, such as projections of one ciphertext.
+    pub fn concat(
+        ciphertext_1: &ShachamCiphertext<G>,
+        ciphertext_2: &ShachamCiphertext<G>,
+    ) -> ShachamCiphertext<G> {
+        assert!(
+            ciphertext_1.r1 == ciphertext_2.r1
+                && ciphertext_1.r2 == ciphertext_2.r2
+                && ciphertext_1.r3 == ciphertext_2.r3
+        );
+
+        let mut e = ciphertext_1.e.clone();
+        e.extend_from_slice(&ciphertext_2.e);
+
+        ShachamCiphertext::<G> {
+            r1: ciphertext_1.r1,
+            r2: ciphertext_1.r2,
+            r3: ciphertext_1.r3,
+            e,
+        }
+    }
+
+    pub fn concat_public_keys(
+        pk_1: &ShachamPublicKey<G>,
+        pk_2: &ShachamPublicKey<G>,
+    ) -> ShachamPublicKey<G> {
+        assert!(pk_1.pp.u == pk_2.pp.u && pk_1.pp.v == pk_2.pp.v && pk_1.pp.w == pk_2.pp.w);
+
+        let mut y = pk_1.y.clone();
+        y.extend_from_slice(&pk_2.y);
+        let mut z = pk_1.z.clone();
+        z.extend_from_slice(&pk_2.z);
+
+        ShachamPublicKey::<G> {
+            pp: pk_1.pp.clone(),
+            y,
+            z,
+        }
+    }
+
+    pub fn concat_secret_keys(
+        sk_1: &ShachamSecretKey<G>,
+        sk_2: &ShachamSecretKey<G>,
+    ) -> ShachamSecretKey<G> {
+        let mut scalar_x = sk_1.scalar_x.clone();
+        scalar_x.extend_from_slice(&sk_2.scalar_x);
+        let mut scalar_y = sk_1.scalar_y.clone();
+        scalar_y.extend_from_slice(&sk_2.scalar_y);
+        let mut scalar_z = sk_1.scalar_z.clone();
+        scalar_z.extend_from_slice(&sk_2.scalar_z);
+
+        ShachamSecretKey::<G> {
+            scalar_x,
+            scalar_y,
+            scalar_z,
+        }
+    }
+
+    /// Permute the slots, so that slot `i` of the result is slot `perm[i]` of the input.
+    /// The result decrypts under the keys permuted in the same way.
+    pub fn permute(ciphertext: &ShachamCiphertext<G>, perm: &[usize]) -> ShachamCiphertext<G> {
+        assert_permutation(perm, ciphertext.e.len());
+        Self::project(ciphertext, perm)
+    }
+
+    pub fn permute_public_key(pk: &ShachamPublicKey<G>, perm: &[usize]) -> ShachamPublicKey<G> {
+        assert_permutation(perm, pk.y.len());
+        Self::project_public_key(pk, perm)
+    }
+
+    pub fn permute_secret_key(sk: &ShachamSecretKey<G>, perm: &[usize]) -> ShachamSecretKey<G> {
+        assert_permutation(perm, sk.scalar_x.len());
+        Self::project_secret_key(sk, perm)
+    }
+
+    /// Decrypt a ciphertext that only contains the slots at `indices`, e.g., one obtained
+    /// from [`Self::project`] after a partial download, using the matching secret scalars.
+    pub fn decrypt_slots(
+        sk: &ShachamSecretKey<G>,
+        ciphertext: &ShachamCiphertext<G>,
+        indices: &[usize],
+    ) -> Vec<G> {
+        assert_eq!(ciphertext.e.len(), indices.len());
+        Self::decrypt(&Self::project_secret_key(sk, indices), ciphertext)
+    }
 }
 
 #[cfg(test)]
@@ -207,4 +345,64 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn test_slot_operations() {
+        let mut rng = ark_std::test_rng();
+        let len = 10;
+
+        let mut pt = Vec::new();
+        for _ in 0..len {
+            pt.push(G1Projective::rand(&mut rng));
+        }
+
+        let pp = ShachamEncryption::<G1Projective>::setup(&mut rng);
+        let (sk, pk) = ShachamEncryption::<G1Projective>::key_generation(&pp, len, &mut rng);
+
+        let ct = ShachamEncryption::encrypt(&pk, &pt, &mut rng);
+
+        // partial download of a subset of the slots
+        let indices = [7, 2, 5];
+        let ct_projected = ShachamEncryption::project(&ct, &indices);
+        let pt_recovered = ShachamEncryption::decrypt_slots(&sk, &ct_projected, &indices);
+        for (j, i) in indices.iter().enumerate() {
+            assert!(
+                pt[*i].eq(&pt_recovered[j]),
+                "Decrypted results of projected ciphertexts do not match the plaintexts."
+            );
+        }
+
+        // concatenation of two projections under the concatenated keys
+        let left = [0, 1, 2, 3];
+        let right = [4, 5, 6, 7, 8, 9];
+        let ct_concat = ShachamEncryption::concat(
+            &ShachamEncryption::project(&ct, &left),
+            &ShachamEncryption::project(&ct, &right),
+        );
+        let sk_concat = ShachamEncryption::concat_secret_keys(
+            &ShachamEncryption::project_secret_key(&sk, &left),
+            &ShachamEncryption::project_secret_key(&sk, &right),
+        );
+        let pt_recovered = ShachamEncryption::decrypt(&sk_concat, &ct_concat);
+        for i in 0..len {
+            assert!(
+                pt[i].eq(&pt_recovered[i]),
+                "Decrypted results of concatenated ciphertexts do not match the plaintexts."
+            );
+        }
+
+        // permutation, followed by rerandomization under the permuted public key
+        let perm = [3, 9, 0, 1, 8, 2, 7, 4, 6, 5];
+        let pk_perm = ShachamEncryption::permute_public_key(&pk, &perm);
+        let sk_perm = ShachamEncryption::permute_secret_key(&sk, &perm);
+        let ct_perm =
+            ShachamEncryption::rerand(&pk_perm, &ShachamEncryption::permute(&ct, &perm), &mut rng);
+        let pt_recovered = ShachamEncryption::decrypt(&sk_perm, &ct_perm);
+        for i in 0..len {
+            assert!(
+                pt[perm[i]].eq(&pt_recovered[i]),
+                "Decrypted results of permuted ciphertexts do not match the plaintexts."
+            );
+        }
+    }
 }
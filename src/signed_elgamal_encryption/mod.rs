@@ -0,0 +1,193 @@
+use crate::elgamal_encryption::{
+    ElGamalCiphertext, ElGamalEncryption, ElGamalPublicKey, ElGamalPublicParameters,
+    ElGamalSecretKey,
+};
+use ark_ec::ProjectiveCurve;
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use ark_std::{marker::PhantomData, vec::Vec, UniformRand};
+use blake2::{Blake2b, Digest};
+
+/// The domain separator for the Fiat-Shamir challenge of the proof of knowledge.
+const CHALLENGE_DOMAIN: &[u8] = b"netherite-signed-elgamal-challenge";
+
+/// An ElGamal ciphertext with a Schnorr proof of knowledge of its randomness,
+/// following Tsiounis-Yung and Schnorr-Jakobsson.
+///
+/// The proof binds all the slots of the ciphertext, so any change to `r` or `e` invalidates it.
+#[derive(Clone)]
+pub struct SignedElGamalCiphertext<G: ProjectiveCurve> {
+    pub ct: ElGamalCiphertext<G>,
+    pub c: G::ScalarField,
+    pub z: G::ScalarField,
+}
+
+pub struct SignedElGamalEncryption<G: ProjectiveCurve> {
+    pub pairing_engine_phantom: PhantomData<G>,
+}
+
+impl<G: ProjectiveCurve> SignedElGamalEncryption<G> {
+    pub fn encrypt<R: ark_std::rand::Rng>(
+        pk: &ElGamalPublicKey<G>,
+        plaintext: &[G],
+        rng: &mut R,
+    ) -> SignedElGamalCiphertext<G> {
+        assert!(plaintext.len() <= pk.y.len());
+
+        let scalar_r = G::ScalarField::rand(rng);
+        let r = pk.pp.g.mul(scalar_r.into_repr());
+
+        let mut e = Vec::<G>::new();
+
+        for (m, y) in plaintext.iter().zip(pk.y.iter()) {
+            e.push(*m + y.mul(scalar_r.into_repr()));
+        }
+        let ct = ElGamalCiphertext::<G> { r, e };
+
+        // Schnorr proof of knowledge of scalar_r such that r = g * scalar_r
+        let scalar_s = G::ScalarField::rand(rng);
+        let a = pk.pp.g.mul(scalar_s.into_repr());
+
+        let c = Self::challenge(&pk.pp, &ct, &a);
+        let z = scalar_s + &(c * &scalar_r);
+
+        SignedElGamalCiphertext::<G> { ct, c, z }
+    }
+
+    pub fn verify(
+        pp: &ElGamalPublicParameters<G>,
+        ciphertext: &SignedElGamalCiphertext<G>,
+    ) -> bool {
+        let a = pp.g.mul(ciphertext.z.into_repr()) - ciphertext.ct.r.mul(ciphertext.c.into_repr());
+
+        Self::challenge(pp, &ciphertext.ct, &a) == ciphertext.c
+    }
+
+    /// Decrypt the ciphertext if the proof verifies, and reject it otherwise.
+    pub fn decrypt(
+        pp: &ElGamalPublicParameters<G>,
+        sk: &ElGamalSecretKey<G>,
+        ciphertext: &SignedElGamalCiphertext<G>,
+    ) -> Option<Vec<G>> {
+        if Self::verify(pp, ciphertext) {
+            Some(ElGamalEncryption::<G>::decrypt(sk, &ciphertext.ct))
+        } else {
+            None
+        }
+    }
+
+    /// Strip the proof, which gives a plain ciphertext that can be rerandomized, e.g., for mixing.
+    /// The result is no longer non-malleable.
+    pub fn strip(ciphertext: &SignedElGamalCiphertext<G>) -> ElGamalCiphertext<G> {
+        ciphertext.ct.clone()
+    }
+
+    fn challenge(
+        pp: &ElGamalPublicParameters<G>,
+        ct: &ElGamalCiphertext<G>,
+        a: &G,
+    ) -> G::ScalarField {
+        let mut bytes = Vec::new();
+        pp.g.serialize(&mut bytes).unwrap();
+        ct.r.serialize(&mut bytes).unwrap();
+        (ct.e.len() as u64).serialize(&mut bytes).unwrap();
+        for e in ct.e.iter() {
+            e.serialize(&mut bytes).unwrap();
+        }
+        a.serialize(&mut bytes).unwrap();
+
+        let mut hasher = Blake2b::new();
+        hasher.update(CHALLENGE_DOMAIN);
+        hasher.update(&bytes);
+
+        // the 512-bit output is reduced modulo the group order, which has negligible bias
+        G::ScalarField::from_le_bytes_mod_order(&hasher.finalize())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::elgamal_encryption::ElGamalEncryption;
+    use crate::signed_elgamal_encryption::SignedElGamalEncryption;
+    use ark_bls12_381::G1Projective;
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let mut rng = ark_std::test_rng();
+        let len = 10;
+
+        let mut pt = Vec::new();
+        for _ in 0..len {
+            pt.push(G1Projective::rand(&mut rng));
+        }
+
+        let pp = ElGamalEncryption::<G1Projective>::setup(&mut rng);
+        let (sk, pk) = ElGamalEncryption::<G1Projective>::key_generation(&pp, len, &mut rng);
+
+        let ct = SignedElGamalEncryption::encrypt(&pk, &pt, &mut rng);
+        let pt_recovered = SignedElGamalEncryption::decrypt(&pp, &sk, &ct).unwrap();
+
+        for i in 0..len {
+            assert!(
+                pt[i].eq(&pt_recovered[i]),
+                "Decrypted results do not match the plaintexts."
+            );
+        }
+    }
+
+    #[test]
+    fn test_mauled_ciphertext() {
+        let mut rng = ark_std::test_rng();
+        let len = 10;
+
+        let mut pt = Vec::new();
+        for _ in 0..len {
+            pt.push(G1Projective::rand(&mut rng));
+        }
+
+        let pp = ElGamalEncryption::<G1Projective>::setup(&mut rng);
+        let (sk, pk) = ElGamalEncryption::<G1Projective>::key_generation(&pp, len, &mut rng);
+
+        let ct = SignedElGamalEncryption::encrypt(&pk, &pt, &mut rng);
+        assert!(SignedElGamalEncryption::verify(&pp, &ct));
+
+        let mut ct_mauled = ct.clone();
+        ct_mauled.ct.e[3] += G1Projective::rand(&mut rng);
+        assert!(SignedElGamalEncryption::decrypt(&pp, &sk, &ct_mauled).is_none());
+
+        let mut ct_mauled = ct.clone();
+        ct_mauled.ct.r += pp.g;
+        assert!(SignedElGamalEncryption::decrypt(&pp, &sk, &ct_mauled).is_none());
+
+        let mut ct_mauled = ct;
+        ct_mauled.ct.e.pop();
+        assert!(SignedElGamalEncryption::decrypt(&pp, &sk, &ct_mauled).is_none());
+    }
+
+    #[test]
+    fn test_strip_and_rerandomize() {
+        let mut rng = ark_std::test_rng();
+        let len = 10;
+
+        let mut pt = Vec::new();
+        for _ in 0..len {
+            pt.push(G1Projective::rand(&mut rng));
+        }
+
+        let pp = ElGamalEncryption::<G1Projective>::setup(&mut rng);
+        let (sk, pk) = ElGamalEncryption::<G1Projective>::key_generation(&pp, len, &mut rng);
+
+        let ct = SignedElGamalEncryption::encrypt(&pk, &pt, &mut rng);
+        let ct_plain = SignedElGamalEncryption::strip(&ct);
+        let ct_rerand = ElGamalEncryption::rerand(&pk, &ct_plain, &mut rng);
+        let pt_recovered = ElGamalEncryption::decrypt(&sk, &ct_rerand);
+
+        for i in 0..len {
+            assert!(
+                pt[i].eq(&pt_recovered[i]),
+                "Decrypted results of stripped ciphertexts do not match the plaintexts."
+            );
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use ark_ec::ProjectiveCurve;
+use ark_ff::{Field, FpParameters, PrimeField};
+use ark_serialize::CanonicalSerialize;
+use ark_std::vec::Vec;
+use blake2::{Blake2b, Digest};
+
+/// The domain separator of every transcript.
+const TRANSCRIPT_DOMAIN: &[u8] = b"netherite-transcript";
+
+/// The statistical distance of the challenges from uniform is at most 2^-SECURITY_LEVEL.
+const SECURITY_LEVEL: usize = 128;
+
+/// A Fiat-Shamir transcript over Blake2b.
+///
+/// Every append is framed with the lengths of its label and its data, so two different sequences of
+/// appends never feed the same bytes to the hash. The challenges are derived from the state in counter mode,
+/// and each challenge is absorbed back into the transcript, so that the next challenge depends on it.
+///
+/// Cloning the transcript gives an independent copy of its state, and [`Transcript::fork`] gives a copy
+/// bound to a label and an index, for provers that run parts of a proof in parallel.
+#[derive(Clone)]
+pub struct Transcript {
+    hasher: Blake2b,
+}
+
+impl Transcript {
+    /// Start a transcript for the protocol with the given label.
+    pub fn new(label: &[u8]) -> Self {
+        let mut hasher = Blake2b::new();
+        hasher.update(TRANSCRIPT_DOMAIN);
+
+        let mut transcript = Self { hasher };
+        transcript.append_message(b"dom-sep", label);
+        transcript
+    }
+
+    pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+        self.hasher.update((label.len() as u64).to_le_bytes());
+        self.hasher.update(label);
+        self.hasher.update((message.len() as u64).to_le_bytes());
+        self.hasher.update(message);
+    }
+
+    /// Append a point in its compressed affine form, so the projective representation does not matter.
+    pub fn append_point<G: ProjectiveCurve>(&mut self, label: &[u8], point: &G) {
+        let mut bytes = Vec::new();
+        point.into_affine().serialize(&mut bytes).unwrap();
+        self.append_message(label, &bytes);
+    }
+
+    pub fn append_field_element<F: Field>(&mut self, label: &[u8], val: &F) {
+        let mut bytes = Vec::new();
+        val.serialize(&mut bytes).unwrap();
+        self.append_message(label, &bytes);
+    }
+
+    /// Fill the buffer with challenge bytes.
+    pub fn challenge_bytes(&mut self, label: &[u8], dest: &mut [u8]) {
+        self.append_message(b"challenge", label);
+        self.hasher.update((dest.len() as u64).to_le_bytes());
+
+        for (i, chunk) in dest.chunks_mut(64).enumerate() {
+            let mut hasher = self.hasher.clone();
+            hasher.update((i as u64).to_le_bytes());
+            let block = hasher.finalize();
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+
+        self.append_message(b"challenge-output", dest);
+    }
+
+    /// Derive a challenge in the field F, by reducing SECURITY_LEVEL more bits than the modulus,
+    /// which has a statistical distance of at most 2^-SECURITY_LEVEL from uniform.
+    pub fn challenge_scalar<F: PrimeField>(&mut self, label: &[u8]) -> F {
+        let num_bits = F::Params::MODULUS_BITS as usize + SECURITY_LEVEL;
+        let mut bytes = vec![0u8; num_bits.div_ceil(8)];
+        self.challenge_bytes(label, &mut bytes);
+
+        F::from_le_bytes_mod_order(&bytes)
+    }
+
+    /// Fork the transcript for the branch with the given index, such as the share of a parallel prover.
+    /// The forks with different labels or indices are independent.
+    pub fn fork(&self, label: &[u8], index: u64) -> Self {
+        let mut transcript = self.clone();
+        transcript.append_message(b"fork", label);
+        transcript.hasher.update(index.to_le_bytes());
+        transcript
+    }
+}
+
+/// Hash the message to a scalar, with a fresh transcript for the domain.
+pub fn hash_to_scalar<F: PrimeField>(domain: &[u8], message: &[u8]) -> F {
+    let mut transcript = Transcript::new(domain);
+    transcript.append_message(b"message", message);
+    transcript.challenge_scalar(b"scalar")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::curve_bn254::{Fr as Bn254Fr, G1Projective as Bn254G1Projective};
+    use crate::curve_bn446::{Fr as Bn446Fr, G2Projective as Bn446G2Projective};
+    use crate::transcript::{hash_to_scalar, Transcript};
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::Zero;
+    use ark_std::UniformRand;
+
+    const REPETITIONS: u64 = 10;
+
+    #[test]
+    fn test_determinism() {
+        let mut rng = ark_std::test_rng();
+
+        for _ in 0..REPETITIONS {
+            let point = Bn254G1Projective::rand(&mut rng);
+            let scalar = Bn254Fr::rand(&mut rng);
+
+            let mut t1 = Transcript::new(b"test");
+            t1.append_point(b"point", &point);
+            t1.append_field_element(b"scalar", &scalar);
+
+            // a different projective representation of the same point
+            let mut t2 = Transcript::new(b"test");
+            t2.append_point(b"point", &point.into_affine().into_projective());
+            t2.append_field_element(b"scalar", &scalar);
+
+            let c1: Bn254Fr = t1.challenge_scalar(b"c");
+            assert_eq!(c1, t2.challenge_scalar::<Bn254Fr>(b"c"));
+
+            // the challenges are absorbed, so the next challenge is different
+            let c2: Bn254Fr = t1.challenge_scalar(b"c");
+            assert_ne!(c1, c2);
+            assert_eq!(c2, t2.challenge_scalar::<Bn254Fr>(b"c"));
+        }
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        let challenge = |f: &dyn Fn(&mut Transcript)| {
+            let mut t = Transcript::new(b"test");
+            f(&mut t);
+            t.challenge_scalar::<Bn446Fr>(b"c")
+        };
+
+        let c = challenge(&|t| t.append_message(b"a", b"bc"));
+        assert_ne!(c, challenge(&|t| t.append_message(b"ab", b"c")));
+        assert_ne!(c, challenge(&|t| t.append_message(b"a", b"bd")));
+        assert_ne!(
+            c,
+            challenge(&|t| {
+                t.append_message(b"a", b"b");
+                t.append_message(b"", b"c");
+            })
+        );
+
+        let mut t = Transcript::new(b"other");
+        t.append_message(b"a", b"bc");
+        assert_ne!(c, t.challenge_scalar::<Bn446Fr>(b"c"));
+
+        let mut t = Transcript::new(b"test");
+        t.append_message(b"a", b"bc");
+        assert_ne!(c, t.challenge_scalar::<Bn446Fr>(b"d"));
+    }
+
+    #[test]
+    fn test_fork() {
+        let mut rng = ark_std::test_rng();
+
+        let mut transcript = Transcript::new(b"test");
+        transcript.append_point(b"point", &Bn446G2Projective::rand(&mut rng));
+        let mut parent = transcript.clone();
+
+        let challenges: Vec<Bn446Fr> = (0..REPETITIONS)
+            .map(|i| transcript.fork(b"prover", i).challenge_scalar(b"c"))
+            .collect();
+        for i in 0..challenges.len() {
+            for j in 0..i {
+                assert_ne!(challenges[i], challenges[j]);
+            }
+        }
+
+        // the forks are reproducible and do not change the parent
+        assert_eq!(
+            challenges[1],
+            transcript
+                .fork(b"prover", 1)
+                .challenge_scalar::<Bn446Fr>(b"c")
+        );
+        assert_ne!(
+            challenges[1],
+            transcript
+                .fork(b"verifier", 1)
+                .challenge_scalar::<Bn446Fr>(b"c")
+        );
+        assert_eq!(
+            parent.challenge_scalar::<Bn446Fr>(b"c"),
+            transcript.challenge_scalar::<Bn446Fr>(b"c")
+        );
+    }
+
+    #[test]
+    fn test_challenge_bytes() {
+        let mut t1 = Transcript::new(b"test");
+        let mut t2 = t1.clone();
+
+        let mut long = [0u8; 200];
+        t1.challenge_bytes(b"c", &mut long);
+        assert!(long[128..].iter().any(|b| *b != 0));
+
+        // the output length is bound to the challenge
+        let mut short = [0u8; 64];
+        t2.challenge_bytes(b"c", &mut short);
+        assert_ne!(long[..64], short[..]);
+    }
+
+    #[test]
+    fn test_hash_to_scalar() {
+        let s: Bn254Fr = hash_to_scalar(b"domain", b"message");
+        assert!(!s.is_zero());
+        assert_eq!(s, hash_to_scalar(b"domain", b"message"));
+        assert_ne!(s, hash_to_scalar(b"domain", b"messagf"));
+        assert_ne!(s, hash_to_scalar(b"domaio", b"message"));
+    }
+}
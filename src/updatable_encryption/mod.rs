@@ -0,0 +1,233 @@
+use crate::shacham_encryption::{
+    ShachamCiphertext, ShachamEncryption, ShachamPublicKey, ShachamSecretKey,
+};
+use ark_ec::ProjectiveCurve;
+use ark_ff::PrimeField;
+use ark_std::{marker::PhantomData, vec::Vec};
+
+/// The update token that moves a Shacham ciphertext from an old key to a new key.
+///
+/// This follows the ciphertext-independent updatable encryption of RISE/SHINE:
+/// the token only depends on the two keys, so the key owner issues it once and the
+/// storage server applies it to every stored ciphertext.
+///
+/// The token carries the differences of the secret scalars, which alone do not decrypt
+/// anything, and the new public key, which is used to rerandomize the updated ciphertext
+/// so that it cannot be linked to the old one.
+#[derive(Clone)]
+pub struct ShachamUpdateToken<G: ProjectiveCurve> {
+    pub delta_x: Vec<G::ScalarField>,
+    pub delta_y: Vec<G::ScalarField>,
+    pub delta_z: Vec<G::ScalarField>,
+    pub pk_new: ShachamPublicKey<G>,
+}
+
+pub struct ShachamUpdatableEncryption<G: ProjectiveCurve> {
+    pub pairing_engine_phantom: PhantomData<G>,
+}
+
+impl<G: ProjectiveCurve> ShachamUpdatableEncryption<G> {
+    /// Generate the token that moves ciphertexts from `sk_old` to `sk_new`.
+    ///
+    /// Panics if the keys do not have the same length, or if `pk_new` is not the public key of `sk_new`
+    /// under its public parameters, since the token would then rerandomize under a foreign key.
+    pub fn token_generation(
+        sk_old: &ShachamSecretKey<G>,
+        sk_new: &ShachamSecretKey<G>,
+        pk_new: &ShachamPublicKey<G>,
+    ) -> ShachamUpdateToken<G> {
+        let len = sk_old.scalar_x.len();
+        for scalars in [
+            &sk_old.scalar_y,
+            &sk_old.scalar_z,
+            &sk_new.scalar_x,
+            &sk_new.scalar_y,
+            &sk_new.scalar_z,
+        ] {
+            assert_eq!(scalars.len(), len);
+        }
+        assert_eq!(pk_new.y.len(), len);
+        assert_eq!(pk_new.z.len(), len);
+
+        let pp = &pk_new.pp;
+        for i in 0..len {
+            assert!(
+                pk_new.y[i]
+                    == pp.u.mul(sk_new.scalar_x[i].into_repr())
+                        + pp.w.mul(sk_new.scalar_z[i].into_repr())
+                    && pk_new.z[i]
+                        == pp.v.mul(sk_new.scalar_y[i].into_repr())
+                            + pp.w.mul(sk_new.scalar_z[i].into_repr()),
+                "The new public key does not match the new secret key."
+            );
+        }
+
+        let mut delta_x = Vec::<G::ScalarField>::new();
+        let mut delta_y = Vec::<G::ScalarField>::new();
+        let mut delta_z = Vec::<G::ScalarField>::new();
+
+        for i in 0..len {
+            delta_x.push(sk_new.scalar_x[i] - &sk_old.scalar_x[i]);
+            delta_y.push(sk_new.scalar_y[i] - &sk_old.scalar_y[i]);
+            delta_z.push(sk_new.scalar_z[i] - &sk_old.scalar_z[i]);
+        }
+
+        ShachamUpdateToken::<G> {
+            delta_x,
+            delta_y,
+            delta_z,
+            pk_new: (*pk_new).clone(),
+        }
+    }
+
+    /// Generate a fresh key pair under the same public parameters, together with the
+    /// token that moves ciphertexts from `sk_old` to the new key.
+    pub fn key_rotation<R: ark_std::rand::Rng>(
+        pk_old: &ShachamPublicKey<G>,
+        sk_old: &ShachamSecretKey<G>,
+        rng: &mut R,
+    ) -> (
+        ShachamSecretKey<G>,
+        ShachamPublicKey<G>,
+        ShachamUpdateToken<G>,
+    ) {
+        let (sk_new, pk_new) =
+            ShachamEncryption::<G>::key_generation(&pk_old.pp, sk_old.scalar_x.len(), rng);
+        let token = Self::token_generation(sk_old, &sk_new, &pk_new);
+
+        (sk_new, pk_new, token)
+    }
+
+    pub fn update<R: ark_std::rand::Rng>(
+        token: &ShachamUpdateToken<G>,
+        ciphertext: &ShachamCiphertext<G>,
+        rng: &mut R,
+    ) -> ShachamCiphertext<G> {
+        assert!(ciphertext.e.len() <= token.delta_x.len());
+        let len = ciphertext.e.len();
+
+        // e_i = m_i + x_i * r1 + y_i * r2 + z_i * r3 under the key layout of Shacham encryption,
+        // so shifting by the scalar differences moves the ciphertext to the new key.
+        let mut e = Vec::<G>::new();
+        for i in 0..len {
+            e.push(
+                ciphertext.e[i]
+                    + ciphertext.r1.mul(token.delta_x[i].into_repr())
+                    + ciphertext.r2.mul(token.delta_y[i].into_repr())
+                    + ciphertext.r3.mul(token.delta_z[i].into_repr()),
+            );
+        }
+
+        let shifted = ShachamCiphertext::<G> {
+            r1: ciphertext.r1,
+            r2: ciphertext.r2,
+            r3: ciphertext.r3,
+            e,
+        };
+
+        ShachamEncryption::<G>::rerand(&token.pk_new, &shifted, rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::shacham_encryption::ShachamEncryption;
+    use crate::updatable_encryption::ShachamUpdatableEncryption;
+    use ark_bls12_381::G1Projective;
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_update() {
+        let mut rng = ark_std::test_rng();
+        let len = 10;
+
+        let mut pt = Vec::new();
+        for _ in 0..len {
+            pt.push(G1Projective::rand(&mut rng));
+        }
+
+        let pp = ShachamEncryption::<G1Projective>::setup(&mut rng);
+        let (sk, pk) = ShachamEncryption::<G1Projective>::key_generation(&pp, len, &mut rng);
+
+        let ct = ShachamEncryption::encrypt(&pk, &pt, &mut rng);
+
+        let (sk_new, _, token) =
+            ShachamUpdatableEncryption::<G1Projective>::key_rotation(&pk, &sk, &mut rng);
+        let ct_new = ShachamUpdatableEncryption::update(&token, &ct, &mut rng);
+
+        assert!(
+            ct_new.r1 != ct.r1 && ct_new.r2 != ct.r2 && ct_new.r3 != ct.r3,
+            "Updated ciphertexts are not rerandomized."
+        );
+
+        let pt_recovered = ShachamEncryption::decrypt(&sk_new, &ct_new);
+
+        for i in 0..len {
+            assert!(
+                pt[i].eq(&pt_recovered[i]),
+                "Decrypted results of updated ciphertexts do not match the plaintexts."
+            );
+        }
+    }
+
+    #[test]
+    fn test_repeated_update() {
+        let mut rng = ark_std::test_rng();
+        let len = 4;
+
+        let mut pt = Vec::new();
+        for _ in 0..len {
+            pt.push(G1Projective::rand(&mut rng));
+        }
+
+        let pp = ShachamEncryption::<G1Projective>::setup(&mut rng);
+        let (mut sk, mut pk) =
+            ShachamEncryption::<G1Projective>::key_generation(&pp, len, &mut rng);
+
+        let mut ct = ShachamEncryption::encrypt(&pk, &pt, &mut rng);
+
+        for _ in 0..3 {
+            let (sk_new, pk_new, token) =
+                ShachamUpdatableEncryption::<G1Projective>::key_rotation(&pk, &sk, &mut rng);
+            ct = ShachamUpdatableEncryption::update(&token, &ct, &mut rng);
+            sk = sk_new;
+            pk = pk_new;
+        }
+
+        let pt_recovered = ShachamEncryption::decrypt(&sk, &ct);
+
+        for i in 0..len {
+            assert!(
+                pt[i].eq(&pt_recovered[i]),
+                "Decrypted results of updated ciphertexts do not match the plaintexts."
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "does not match the new secret key")]
+    fn test_token_generation_mismatched_key() {
+        let mut rng = ark_std::test_rng();
+
+        let pp = ShachamEncryption::<G1Projective>::setup(&mut rng);
+        let (sk_old, _) = ShachamEncryption::<G1Projective>::key_generation(&pp, 3, &mut rng);
+        let (sk_new, _) = ShachamEncryption::<G1Projective>::key_generation(&pp, 3, &mut rng);
+        let (_, pk_other) = ShachamEncryption::<G1Projective>::key_generation(&pp, 3, &mut rng);
+
+        ShachamUpdatableEncryption::token_generation(&sk_old, &sk_new, &pk_other);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_token_generation_short_key() {
+        let mut rng = ark_std::test_rng();
+
+        let pp = ShachamEncryption::<G1Projective>::setup(&mut rng);
+        let (sk_old, _) = ShachamEncryption::<G1Projective>::key_generation(&pp, 3, &mut rng);
+        let (mut sk_new, pk_new) =
+            ShachamEncryption::<G1Projective>::key_generation(&pp, 3, &mut rng);
+        sk_new.scalar_z.pop();
+
+        ShachamUpdatableEncryption::token_generation(&sk_old, &sk_new, &pk_new);
+    }
+}
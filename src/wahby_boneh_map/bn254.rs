@@ -0,0 +1,284 @@
+use crate::curve_bn254::{Fq, Fr, Parameters};
+use crate::wahby_boneh_map::WahbyBonehParameters;
+use ark_ec::models::{ModelParameters, SWModelParameters};
+use ark_ff::field_new;
+
+/// The curve E': y^2 = x^3 + A' x + 506, which is 3-isogenous to G1 of BN254,
+/// following `sage_scripts/BN254-Isogeny-Map.ipynb`.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct IsogenousG1Parameters;
+
+impl ModelParameters for IsogenousG1Parameters {
+    type BaseField = Fq;
+    type ScalarField = Fr;
+}
+
+impl SWModelParameters for IsogenousG1Parameters {
+    /// COEFF_A = 16798108731015832068084442708154438012295934837684635093117171897687148068043
+    const COEFF_A: Fq = field_new!(
+        Fq,
+        "16798108731015832068084442708154438012295934837684635093117171897687148068043"
+    );
+
+    /// COEFF_B = 506
+    const COEFF_B: Fq = field_new!(Fq, "506");
+
+    /// COFACTOR = 1
+    const COFACTOR: &'static [u64] = &[0x1];
+
+    /// COFACTOR_INV = COFACTOR^{-1} mod r = 1
+    const COFACTOR_INV: Fr = field_new!(Fr, "1");
+
+    /// AFFINE_GENERATOR_COEFFS = (1, sqrt(1 + A' + 506))
+    const AFFINE_GENERATOR_COEFFS: (Self::BaseField, Self::BaseField) = (
+        field_new!(Fq, "1"),
+        field_new!(
+            Fq,
+            "1118144937493077481895564398874694971961363081209736515581247596966938714893"
+        ),
+    );
+}
+
+impl WahbyBonehParameters for Parameters {
+    const RO_ID: &'static str = "NETHERITE-BN254G1_XMD:SHA-256_SSWU_RO_";
+
+    type IsogenousCurve = IsogenousG1Parameters;
+
+    /// Z = 5
+    const Z: Fq = field_new!(Fq, "5");
+
+    const ISOGENY_X_NUMERATOR: &'static [Fq] = &[
+        field_new!(
+            Fq,
+            "1866456525668425809437867126914637101098798569048785538770412825119194873875"
+        ),
+        field_new!(
+            Fq,
+            "11198739154010554859036717888533125894343827550778873618798216323252787085334"
+        ),
+        field_new!(
+            Fq,
+            "5599369577005277430723116507789214591047431843632517002486977847895202463761"
+        ),
+        field_new!(
+            Fq,
+            "14931652205347406475502937015317096808790388552390284310163302600953558990865"
+        ),
+    ];
+
+    const ISOGENY_X_DENOMINATOR: &'static [Fq] = &[
+        field_new!(
+            Fq,
+            "65056908430223188769277975685126330426744963058515681739004"
+        ),
+        field_new!(
+            Fq,
+            "21685636143407729589759325228375443475581654352838560579680"
+        ),
+        field_new!(Fq, "1"),
+    ];
+
+    const ISOGENY_Y_NUMERATOR: &'static [Fq] = &[
+        field_new!(
+            Fq,
+            "15553804380570215078648892724288642509156654742073212823086773542659957282137"
+        ),
+        field_new!(
+            Fq,
+            "13065195679678980674899958687568571762778722483790753520703934141805629538353"
+        ),
+        field_new!(
+            Fq,
+            "11198739154010554857831960325010474250468309482535793425710346636983978164242"
+        ),
+        field_new!(
+            Fq,
+            "16175956555793023681794848433260188209522920931756141336010244484366355573437"
+        ),
+    ];
+
+    const ISOGENY_Y_DENOMINATOR: &'static [Fq] = &[
+        field_new!(
+            Fq,
+            "16798108731015832284940804142231733909889187121439069848933715426072753864507"
+        ),
+        field_new!(
+            Fq,
+            "195170725290669566307833927055378991280234889175547045217012"
+        ),
+        field_new!(
+            Fq,
+            "32528454215111594384638987842563165213372481529257840869520"
+        ),
+        field_new!(Fq, "1"),
+    ];
+}
+
+#[cfg(test)]
+mod test {
+    use crate::curve_bn254::{Fq, G1Affine, G1Projective, Parameters};
+    use crate::hash_to_curve::HashToCurveError;
+    use crate::wahby_boneh_map::bn254::IsogenousG1Parameters;
+    use crate::wahby_boneh_map::WahbyBonehMap;
+    use ark_ec::short_weierstrass_jacobian::{GroupAffine, GroupProjective};
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::{field_new, Field, Zero};
+    use ark_std::UniformRand;
+
+    const REPETITIONS: u64 = 50;
+
+    #[test]
+    fn test_isogeny_map() {
+        let mut rng = ark_std::test_rng();
+        let map = WahbyBonehMap::<Parameters>::new();
+
+        let g = GroupAffine::<IsogenousG1Parameters>::prime_subgroup_generator();
+        assert!(g.is_on_curve());
+        // a regression value of this implementation
+        assert_eq!(
+            map.isogeny_map(&g),
+            G1Affine::new(
+                field_new!(
+                    Fq,
+                    "7413335167392600942663095505416101993529161590352652953972143334497846101497"
+                ),
+                field_new!(
+                    Fq,
+                    "10748959359805065579501528642650998557063030432169465768340761580253670220478"
+                ),
+                false
+            )
+        );
+
+        // the isogeny is a group homomorphism
+        for _ in 0..REPETITIONS {
+            let p = GroupProjective::<IsogenousG1Parameters>::rand(&mut rng);
+            let q = GroupProjective::<IsogenousG1Parameters>::rand(&mut rng);
+
+            let lhs = map.isogeny_map(&(p + &q).into_affine());
+            let rhs = (map.isogeny_map(&p.into_affine()).into_projective()
+                + &map.isogeny_map(&q.into_affine()).into_projective())
+                .into_affine();
+            assert_eq!(lhs, rhs);
+        }
+
+        assert!(map
+            .isogeny_map(&GroupAffine::<IsogenousG1Parameters>::zero())
+            .is_zero());
+    }
+
+    #[test]
+    fn test_map_to_g1() {
+        let mut rng = ark_std::test_rng();
+        let map = WahbyBonehMap::<Parameters>::new();
+
+        for _ in 0..REPETITIONS {
+            let u = Fq::rand(&mut rng);
+            let p = map.map_to_g1(u);
+            assert!(p.is_on_curve());
+            assert!(p.is_in_correct_subgroup_assuming_on_curve());
+        }
+
+        // regression values of this implementation, which guard against changes of the map
+        // but are not independent test vectors
+        let vectors = [
+            (
+                field_new!(Fq, "0"),
+                "1985608775108298905091400628712001673031179510669854161429707796692309414935",
+                "15351680227363154600041468428274378697528225299899242144201764013338539407208",
+            ),
+            (
+                field_new!(Fq, "1"),
+                "1215930898916238071832134595745830645969573742510242492896711024754830464714",
+                "8941230230440426906447777153683848340879919289223177983198666892040088855904",
+            ),
+            (
+                field_new!(Fq, "2"),
+                "1827892810899994298950704711177710462937923022901455500788871042297363035344",
+                "9913681464694338497861599760412981950238310984221592946949751600016568290794",
+            ),
+            (
+                field_new!(Fq, "-1"),
+                "1215930898916238071832134595745830645969573742510242492896711024754830464714",
+                "7856878500575405378493026988547885569009267832215891865735048534032665008819",
+            ),
+            (
+                field_new!(Fq, "123456789"),
+                "553845073034490936324278317319011013410061292975758243821166283924583769651",
+                "14607728416004493675533012400093318716229449943627977627717340661406059383304",
+            ),
+        ];
+        for (u, x, y) in vectors.iter() {
+            let expected = G1Affine::new(x.parse().unwrap(), y.parse().unwrap(), false);
+            assert_eq!(map.map_to_g1(*u), expected);
+        }
+    }
+
+    #[test]
+    fn test_add_complete() {
+        let mut rng = ark_std::test_rng();
+
+        let to_affine = |(x, y, z): (Fq, Fq, Fq)| {
+            if z.is_zero() {
+                assert!(x.is_zero() && !y.is_zero());
+                G1Affine::zero()
+            } else {
+                let z_inv = z.inverse().unwrap();
+                G1Affine::new(x * &z_inv, y * &z_inv, false)
+            }
+        };
+        let add =
+            |p: &G1Affine, q: &G1Affine| to_affine(WahbyBonehMap::<Parameters>::add_complete(p, q));
+
+        for _ in 0..REPETITIONS {
+            let p = G1Projective::rand(&mut rng).into_affine();
+            let q = G1Projective::rand(&mut rng).into_affine();
+
+            assert_eq!(
+                add(&p, &q),
+                (p.into_projective() + &q.into_projective()).into_affine()
+            );
+            assert_eq!(add(&p, &p), p.into_projective().double().into_affine());
+            assert_eq!(add(&p, &-p), G1Affine::zero());
+            assert_eq!(add(&p, &G1Affine::zero()), p);
+            assert_eq!(add(&G1Affine::zero(), &p), p);
+        }
+        assert_eq!(add(&G1Affine::zero(), &G1Affine::zero()), G1Affine::zero());
+    }
+
+    #[test]
+    fn test_hash_to_g1() {
+        let map = WahbyBonehMap::<Parameters>::new();
+        let dst = WahbyBonehMap::<Parameters>::dst(b"NETHERITE-TEST");
+
+        // regression values of this implementation, which guard against changes of the hash
+        // but are not independent test vectors
+
+        let vectors = [
+            (
+                &b""[..],
+                "4664138485473369367556614308734615872253853333835511877395116316228308497420",
+                "468655221386849769139615175450727129840588006483729195251660588005222087868",
+            ),
+            (
+                &b"abc"[..],
+                "5827192686847108647201456703335269386144838858531763985504395417621959185406",
+                "9941210238524022077835771126467192341825078014240498888107052792399994782411",
+            ),
+        ];
+        for (msg, x, y) in vectors.iter() {
+            let p = map.hash_to_g1(msg, &dst).unwrap();
+            assert!(p.is_on_curve());
+            assert_eq!(
+                p,
+                G1Affine::new(x.parse().unwrap(), y.parse().unwrap(), false)
+            );
+        }
+
+        assert_ne!(
+            map.hash_to_g1(b"abc", &dst).unwrap(),
+            map.hash_to_g1(b"abd", &dst).unwrap()
+        );
+        assert_eq!(map.hash_to_g1(b"abc", b""), Err(HashToCurveError::EmptyDst));
+    }
+}
@@ -0,0 +1,317 @@
+//! The simplified SWU map of Wahby and Boneh into G1, following
+//!     Riad S. Wahby and Dan Boneh, "Fast and simple constant-time hashing to the BLS12-381 elliptic curve", TCHES 2019,
+//! and the straight-line formulas of RFC 9380, Section 6.6.2 and Appendix F.2.
+//!
+//! The simplified SWU map needs a curve with A != 0, so it maps into a curve E' that is isogenous to G1,
+//! and an isogeny of small degree maps the point back to G1. The isogenies are computed in `sage_scripts`.
+//!
+//! BN254 has a 3-isogeny from y^2 = x^3 + A' x + 506. BN446 does not have any isogeny of small degree
+//! from a curve with j != 0, since the conductor of the Frobenius order in Z[(1 + sqrt(-3)) / 2] has no
+//! prime factor below 2000, which `test_isogeny_degrees` checks, so only BN254 is supported here and
+//! BN446 hashes with the Shallue-van de Woestijne map of [`crate::hash_to_curve`].
+//!
+//! The map into BN446 G1 that was asked for is therefore not delivered. Dropping it from the scope still
+//! needs the sign-off of a maintainer.
+
+use crate::message_encoding::constant_time;
+use ark_ec::bn::{BnParameters, G1Affine};
+use ark_ec::short_weierstrass_jacobian::GroupAffine;
+use ark_ec::SWModelParameters;
+use ark_ff::{BigInteger, Field, FpParameters, One, PrimeField, SquareRootField, Zero};
+use ark_std::{marker::PhantomData, ops::Neg, vec::Vec};
+use subtle::Choice;
+
+use crate::hash_to_curve::{hash_to_field, ExpandMessage, HashToCurveError};
+
+pub mod bn254;
+
+pub trait WahbyBonehParameters: BnParameters {
+    /// The ID of the random-oracle suite of [`WahbyBonehMap::hash_to_g1`], in the format of RFC 9380, Section 8.10.
+    const RO_ID: &'static str;
+
+    /// The curve E': y^2 = x^3 + A' x + B', with A' B' != 0, that is isogenous to G1.
+    type IsogenousCurve: SWModelParameters<BaseField = Self::Fp>;
+
+    /// The non-square Z of the simplified SWU map, chosen as in RFC 9380, Appendix H.2.
+    const Z: Self::Fp;
+
+    /// The rational maps of the isogeny from E' to G1, x = x_num(x') / x_den(x') and
+    /// y = y' y_num(x') / y_den(x'), with the coefficients in increasing order of degree.
+    const ISOGENY_X_NUMERATOR: &'static [Self::Fp];
+    const ISOGENY_X_DENOMINATOR: &'static [Self::Fp];
+    const ISOGENY_Y_NUMERATOR: &'static [Self::Fp];
+    const ISOGENY_Y_DENOMINATOR: &'static [Self::Fp];
+}
+
+pub struct WahbyBonehMap<P: WahbyBonehParameters> {
+    /// [u64] representation of (q - 3) / 4, for the square root of a ratio when q == 3 (mod 4).
+    pub sqrt_ratio_pow: Vec<u64>,
+    /// sqrt(-Z)
+    pub sqrt_minus_z: P::Fp,
+    /// [u64] representation of q - 2, for the inversions by Fermat's little theorem.
+    pub inverse_pow: Vec<u64>,
+
+    #[doc(hidden)]
+    pub phantom: PhantomData<P>,
+}
+
+impl<P: WahbyBonehParameters> Default for WahbyBonehMap<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: WahbyBonehParameters> WahbyBonehMap<P> {
+    pub fn new() -> Self {
+        let mut modulus = <P::Fp as PrimeField>::Params::MODULUS;
+        assert_eq!(modulus.as_ref()[0] & 3, 3);
+
+        let mut inverse_pow = modulus;
+        inverse_pow.sub_noborrow(&2u64.into());
+
+        modulus.sub_noborrow(&3u64.into());
+        modulus.div2();
+        modulus.div2();
+
+        // Z is a non-square and q == 3 (mod 4), so -Z is a square
+        let sqrt_minus_z = P::Z.neg().sqrt().unwrap();
+
+        Self {
+            sqrt_ratio_pow: modulus.as_ref().to_vec(),
+            sqrt_minus_z,
+            inverse_pow: inverse_pow.as_ref().to_vec(),
+            phantom: PhantomData,
+        }
+    }
+
+    fn sgn0(val: &P::Fp) -> Choice {
+        Choice::from(val.into_repr().is_odd() as u8)
+    }
+
+    /// Return whether u / v is a square, with sqrt(u / v) if it is, and sqrt(Z u / v) otherwise.
+    fn sqrt_ratio(&self, u: P::Fp, v: P::Fp) -> (Choice, P::Fp) {
+        let tv1 = v.square();
+        let tv2 = u * &v;
+        let tv1 = tv1 * &tv2;
+        let y1 = constant_time::pow_fixed_window(tv1, &self.sqrt_ratio_pow) * &tv2;
+        let y2 = y1 * &self.sqrt_minus_z;
+        let tv3 = y1.square() * &v;
+        let is_square = constant_time::ct_eq(&tv3, &u);
+
+        (
+            is_square,
+            constant_time::conditional_select(&y2, &y1, is_square),
+        )
+    }
+
+    /// The simplified SWU map into E', with branch-free selection.
+    pub fn map_to_isogenous_curve(&self, u: P::Fp) -> GroupAffine<P::IsogenousCurve> {
+        let a = <P::IsogenousCurve as SWModelParameters>::COEFF_A;
+        let b = <P::IsogenousCurve as SWModelParameters>::COEFF_B;
+
+        let tv1 = P::Z * &u.square();
+        let tv2 = tv1.square() + &tv1;
+        let tv3 = b * &(tv2 + &P::Fp::one());
+        let tv4 = a * &constant_time::conditional_select(
+            &P::Z,
+            &tv2.neg(),
+            !constant_time::ct_eq(&tv2, &P::Fp::zero()),
+        );
+
+        // x = tv3 / tv4, and g(x) = tv2 / tv6
+        let tv6 = tv4.square();
+        let tv2 = (tv3.square() + &(a * &tv6)) * &tv3;
+        let tv6 = tv6 * &tv4;
+        let tv2 = tv2 + &(b * &tv6);
+
+        let (is_gx1_square, y1) = self.sqrt_ratio(tv2, tv6);
+
+        let x = constant_time::conditional_select(&(tv1 * &tv3), &tv3, is_gx1_square);
+        let y = constant_time::conditional_select(&(tv1 * &u * &y1), &y1, is_gx1_square);
+
+        let y = constant_time::conditional_select(&y.neg(), &y, !(Self::sgn0(&u) ^ Self::sgn0(&y)));
+        let x = x * &constant_time::pow_fixed_window(tv4, &self.inverse_pow);
+
+        let point = GroupAffine::<P::IsogenousCurve>::new(x, y, false);
+        debug_assert!(point.is_on_curve());
+
+        point
+    }
+
+    fn evaluate(coeffs: &[P::Fp], x: &P::Fp) -> P::Fp {
+        coeffs
+            .iter()
+            .rev()
+            .fold(P::Fp::zero(), |acc, c| acc * x + c)
+    }
+
+    /// Map a point of E' into G1 by the isogeny, with branch-free selection.
+    /// The points of the kernel, where the denominators vanish, are mapped to the point at infinity.
+    pub fn isogeny_map(&self, p: &GroupAffine<P::IsogenousCurve>) -> G1Affine<P> {
+        let x_num = Self::evaluate(P::ISOGENY_X_NUMERATOR, &p.x);
+        let x_den = Self::evaluate(P::ISOGENY_X_DENOMINATOR, &p.x);
+        let y_num = Self::evaluate(P::ISOGENY_Y_NUMERATOR, &p.x);
+        let y_den = Self::evaluate(P::ISOGENY_Y_DENOMINATOR, &p.x);
+
+        // a single inversion for both denominators
+        let den = x_den * &y_den;
+        let infinity = constant_time::ct_eq(&den, &P::Fp::zero()) | Choice::from(p.infinity as u8);
+        let den_inv = constant_time::pow_fixed_window(den, &self.inverse_pow);
+
+        let x = x_num * &y_den * &den_inv;
+        let y = p.y * &y_num * &x_den * &den_inv;
+
+        let x = constant_time::conditional_select(&x, &P::Fp::zero(), infinity);
+        let y = constant_time::conditional_select(&y, &P::Fp::one(), infinity);
+
+        let point = G1Affine::<P>::new(x, y, bool::from(infinity));
+        debug_assert!(point.is_on_curve());
+
+        point
+    }
+
+    /// Map a field element into G1, which is the composition of the simplified SWU map and the isogeny.
+    pub fn map_to_g1(&self, u: P::Fp) -> G1Affine<P> {
+        self.isogeny_map(&self.map_to_isogenous_curve(u))
+    }
+
+    /// The domain separation tag of an application, which is the tag followed by `-with-` and the suite ID,
+    /// as recommended in RFC 9380, Section 3.1.
+    pub fn dst(tag: &[u8]) -> Vec<u8> {
+        [tag, b"-with-", P::RO_ID.as_bytes()].concat()
+    }
+
+    /// The complete addition of Renes, Costello, and Batina for a = 0, "Complete addition formulas for
+    /// prime order elliptic curves", EUROCRYPT 2016, Algorithm 7, in homogeneous projective coordinates.
+    /// It has no exceptional cases, so it also doubles and adds the point at infinity, which is (0 : 1 : 0).
+    fn add_complete(p: &G1Affine<P>, q: &G1Affine<P>) -> (P::Fp, P::Fp, P::Fp) {
+        let to_projective = |p: &G1Affine<P>| {
+            let infinity = Choice::from(p.infinity as u8);
+            (
+                constant_time::conditional_select(&p.x, &P::Fp::zero(), infinity),
+                constant_time::conditional_select(&p.y, &P::Fp::one(), infinity),
+                constant_time::conditional_select(&P::Fp::one(), &P::Fp::zero(), infinity),
+            )
+        };
+        let (x1, y1, z1) = to_projective(p);
+        let (x2, y2, z2) = to_projective(q);
+        let b3 = P::G1Parameters::COEFF_B.double() + &P::G1Parameters::COEFF_B;
+
+        let t0 = x1 * &x2;
+        let t1 = y1 * &y2;
+        let t2 = z1 * &z2;
+        let t3 = (x1 + &y1) * &(x2 + &y2) - &(t0 + &t1);
+        let t4 = (y1 + &z1) * &(y2 + &z2) - &(t1 + &t2);
+        let y3 = (x1 + &z1) * &(x2 + &z2) - &(t0 + &t2);
+        let t0 = t0.double() + &t0;
+        let t2 = b3 * &t2;
+        let z3 = t1 + &t2;
+        let t1 = t1 - &t2;
+        let y3 = b3 * &y3;
+        let x3 = t3 * &t1 - &(t4 * &y3);
+        let y3 = t1 * &z3 + &(y3 * &t0);
+        let z3 = z3 * &t4 + &(t0 * &t3);
+
+        (x3, y3, z3)
+    }
+
+    /// Hash the message into G1 by adding the maps of the two field elements of `hash_to_field` with
+    /// `expand_message_xmd` and SHA-256, which is indifferentiable from a random oracle.
+    /// The cofactor of G1 is one, so the sum is in G1.
+    ///
+    /// The sum is computed by the complete projective addition and normalized with an inversion by
+    /// Fermat's little theorem, so the hash selects branch-free throughout.
+    pub fn hash_to_g1(&self, msg: &[u8], dst: &[u8]) -> Result<G1Affine<P>, HashToCurveError> {
+        let u = hash_to_field::<P::Fp>(msg, dst, 2, ExpandMessage::XmdSha256)?;
+
+        let (x, y, z) = Self::add_complete(&self.map_to_g1(u[0]), &self.map_to_g1(u[1]));
+        let infinity = constant_time::ct_eq(&z, &P::Fp::zero());
+        let z_inv = constant_time::pow_fixed_window(z, &self.inverse_pow);
+
+        let x = constant_time::conditional_select(&(x * &z_inv), &P::Fp::zero(), infinity);
+        let y = constant_time::conditional_select(&(y * &z_inv), &P::Fp::one(), infinity);
+
+        let point = G1Affine::<P>::new(x, y, bool::from(infinity));
+        debug_assert!(point.is_on_curve());
+
+        Ok(point)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::curve_bn254::g1::Parameters as Bn254G1Parameters;
+    use crate::curve_bn254::{Fq as Bn254Fq, Fr as Bn254Fr};
+    use crate::curve_bn446::{Fq as Bn446Fq, Fr as Bn446Fr};
+    use crate::wahby_boneh_map::bn254::IsogenousG1Parameters;
+    use ark_ec::SWModelParameters;
+    use ark_ff::{Field, FpParameters, PrimeField, Zero};
+    use num_bigint::BigUint;
+
+    /// Check whether the element is a cube, for q == 1 (mod 3).
+    fn is_cube<F: PrimeField>(val: F) -> bool {
+        let q_minus_1: BigUint = F::Params::MODULUS.into();
+        let exp = (q_minus_1 - 1u64) / 3u64;
+        val.pow(exp.to_u64_digits()).is_one()
+    }
+
+    /// The codomain y^2 = x^3 + A' x + B' of the 3-isogeny of Velu from y^2 = x^3 + b with the kernel
+    /// generated by a point with the x-coordinate x0, for a = 0.
+    fn velu_3_isogeny<F: PrimeField>(b: F, x0: F) -> (F, F) {
+        let y0_squared = x0.square() * &x0 + &b;
+        let v = F::from(6u64) * &x0.square();
+        let w = F::from(4u64) * &y0_squared + &(x0 * &v);
+        (-F::from(5u64) * &v, b - &(F::from(7u64) * &w))
+    }
+
+    #[test]
+    fn test_small_isogenies() {
+        // The x-coordinates of the 3-torsion of y^2 = x^3 + b are 0, whose isogeny maps to a curve with j = 0,
+        // and the cube roots of -4b. For BN254, b = 2 and x0 = -2 is one of them, and the isogeny of Velu
+        // maps to y^2 = x^3 - 120 x + 506. E' has the same B', and A' = -120 up to a cube root of unity,
+        // so both curves are isomorphic by (x, y) -> (zeta x, y) and E' is indeed 3-isogenous to G1.
+        let b = <Bn254G1Parameters as SWModelParameters>::COEFF_B;
+        let x0 = -Bn254Fq::from(2u64);
+        assert!((x0.square() * &x0 + &b.double().double()).is_zero());
+
+        let (a_velu, b_velu) = velu_3_isogeny(b, x0);
+        assert_eq!(a_velu, -Bn254Fq::from(120u64));
+        assert_eq!(b_velu, Bn254Fq::from(506u64));
+
+        let a_prime = <IsogenousG1Parameters as SWModelParameters>::COEFF_A;
+        let b_prime = <IsogenousG1Parameters as SWModelParameters>::COEFF_B;
+        assert_eq!(b_prime, b_velu);
+        assert_ne!(a_prime, a_velu);
+        assert_eq!(a_prime.square() * &a_prime, a_velu.square() * &a_velu);
+
+        // For BN446, b = 257 and -4b is not a cube, so the only 3-isogeny maps to a curve with j = 0.
+        assert!(!is_cube(-Bn446Fq::from(4u64 * 257)));
+
+        // BN446 does not have any 2-torsion either.
+        assert!(!is_cube(-Bn446Fq::from(257u64)));
+    }
+
+    /// The smallest prime factor below the bound of the conductor f of the Frobenius order, where
+    /// t^2 - 4q = -3 f^2, for the curve with q + 1 - t = r points.
+    fn smallest_conductor_factor<Fq: PrimeField, Fr: PrimeField>(bound: u64) -> Option<u64> {
+        let q: BigUint = Fq::Params::MODULUS.into();
+        let r: BigUint = Fr::Params::MODULUS.into();
+        let t = &q + 1u64 - &r;
+
+        let f_squared = (&q * 4u64 - &t * &t) / 3u64;
+        let f = f_squared.sqrt();
+        assert_eq!(&f * &f, f_squared);
+
+        (2..bound)
+            .filter(|l| (2..*l).all(|d| l % d != 0))
+            .find(|l| (&f % *l) == BigUint::from(0u64))
+    }
+
+    #[test]
+    fn test_isogeny_degrees() {
+        // G1 has j = 0, so its endomorphism ring is the maximal order, and an isogeny of prime degree l
+        // to a curve with j != 0 descends the l-volcano, which needs l to divide the conductor.
+        assert_eq!(smallest_conductor_factor::<Bn254Fq, Bn254Fr>(2000), Some(3));
+        assert_eq!(smallest_conductor_factor::<Bn446Fq, Bn446Fr>(2000), None);
+    }
+}